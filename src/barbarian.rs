@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use rand::{prelude::SliceRandom, thread_rng};
+
+use crate::{
+    board::Board,
+    building::{Building, BuildingSlot, BuildingType},
+    color::{PlayerColor, COLOR_COUNT},
+    commodity::Commodity,
+    knight::KnightSlot,
+    menu::{in_game, GameSetup, Ruleset},
+    resource::produce_resources,
+    turn::{Players, Turn},
+    ui::{BarbarianText, EventDieUi, PlayerHeading},
+};
+
+pub struct BarbarianPlugin;
+
+impl Plugin for BarbarianPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Barbarians>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                // Barbarians attack before anyone gets their resources
+                .with_system(roll_event_die.before(produce_resources))
+                .with_system(update_barbarian_ui),
+        );
+    }
+}
+
+/// A side of the event die, which is rolled along with the other dice in Cities & Knights
+#[derive(Clone, Copy)]
+enum EventDie {
+    /// The barbarians sail one step closer
+    Ship,
+    /// A city gate in the color of the track that the commodity pays for.
+    /// These would hand out progress cards, which aren't in the game yet.
+    Gate(Commodity),
+}
+
+const EVENT_DIE: [EventDie; 6] = [
+    EventDie::Ship,
+    EventDie::Ship,
+    EventDie::Ship,
+    EventDie::Gate(Commodity::Paper),
+    EventDie::Gate(Commodity::Cloth),
+    EventDie::Gate(Commodity::Coin),
+];
+
+impl EventDie {
+    fn image(self) -> &'static str {
+        match self {
+            Self::Ship => "event_ship.png",
+            Self::Gate(Commodity::Paper) => "event_science.png",
+            Self::Gate(Commodity::Cloth) => "event_trade.png",
+            Self::Gate(Commodity::Coin) => "event_politics.png",
+        }
+    }
+}
+
+/// How many ship rolls it takes for the barbarians to reach the island
+pub const BARBARIAN_DISTANCE: u8 = 7;
+
+/// How an attack went. Strength is the number of cities on the island,
+/// and defense is the total level of the active knights.
+#[derive(Clone, Copy)]
+pub enum Attack {
+    /// The player who defended the most, if there's only one, is the defender of the island
+    Repelled {
+        strength: usize,
+        defense: usize,
+        defender: Option<PlayerColor>,
+    },
+    /// The players who defended the least each lose a city
+    Won {
+        strength: usize,
+        defense: usize,
+        cities_lost: usize,
+    },
+}
+
+#[derive(Default)]
+pub struct Barbarians {
+    /// How many steps the barbarians have sailed toward the island
+    pub position: u8,
+    /// The last attack, until the next event die roll
+    pub last_attack: Option<Attack>,
+    /// Victory points for defending the island.
+    /// Indexed by a `PlayerColor as usize`.
+    pub defender_points: [u8; COLOR_COUNT],
+}
+
+/// In Cities & Knights, roll the event die along with the others, and attack if the barbarians arrive
+fn roll_event_die(
+    mut buildings: Query<&mut BuildingSlot>,
+    mut knights: Query<&mut KnightSlot>,
+    mut event_dice: Query<&mut UiImage, With<EventDieUi>>,
+    board: Res<Board>,
+    players: Res<Players>,
+    setup: Res<GameSetup>,
+    assets: Res<AssetServer>,
+    turn: Res<Turn>,
+    mut barbarians: ResMut<Barbarians>,
+) {
+    if !turn.is_changed()
+        || !matches!(*turn, Turn::Production { .. })
+        || setup.ruleset != Ruleset::CitiesAndKnights
+    {
+        return;
+    }
+
+    let roll = *EVENT_DIE.choose(&mut thread_rng()).unwrap();
+
+    for mut image in event_dice.iter_mut() {
+        *image = assets.load(roll.image()).into();
+    }
+
+    barbarians.last_attack = None;
+
+    if let EventDie::Gate(_) = roll {
+        return;
+    }
+
+    barbarians.position += 1;
+    if barbarians.position < BARBARIAN_DISTANCE {
+        return;
+    }
+
+    barbarians.position = 0;
+
+    let mut cities = [0; COLOR_COUNT];
+    for building in board.buildings.iter() {
+        if let Some(Building {
+            building_type: BuildingType::City,
+            color,
+        }) = **buildings.get(*building).unwrap()
+        {
+            cities[color as usize] += 1;
+        }
+    }
+
+    let mut defenses = [0; COLOR_COUNT];
+    for knight in knights.iter() {
+        if let Some(knight) = **knight {
+            if knight.active {
+                defenses[knight.color as usize] += knight.level as usize;
+            }
+        }
+    }
+
+    let strength = cities.iter().sum();
+    let defense = defenses.iter().sum();
+
+    barbarians.last_attack = Some(if defense >= strength {
+        let most = players
+            .iter()
+            .map(|color| defenses[*color as usize])
+            .max()
+            .unwrap_or(0);
+        let mut defenders = players
+            .iter()
+            .filter(|color| most > 0 && defenses[**color as usize] == most);
+        let defender = match (defenders.next(), defenders.next()) {
+            (Some(defender), None) => Some(*defender),
+            _ => None,
+        };
+
+        if let Some(defender) = defender {
+            barbarians.defender_points[defender as usize] += 1;
+        }
+
+        Attack::Repelled {
+            strength,
+            defense,
+            defender,
+        }
+    } else {
+        // Players without cities have nothing to lose, so they're left out
+        let least = players
+            .iter()
+            .filter(|color| cities[**color as usize] > 0)
+            .map(|color| defenses[*color as usize])
+            .min()
+            .unwrap_or(0);
+        let mut cities_lost = 0;
+
+        for &color in players.iter() {
+            if cities[color as usize] == 0 || defenses[color as usize] != least {
+                continue;
+            }
+
+            // The first city on the board is knocked down to a settlement
+            for building in board.buildings.iter() {
+                let mut building = buildings.get_mut(*building).unwrap();
+
+                if let Some(Building {
+                    building_type: BuildingType::City,
+                    color: other,
+                }) = **building
+                {
+                    if other == color {
+                        **building = Some(Building {
+                            building_type: BuildingType::Settlement,
+                            color,
+                        });
+                        cities_lost += 1;
+                        break;
+                    }
+                }
+            }
+        }
+
+        Attack::Won {
+            strength,
+            defense,
+            cities_lost,
+        }
+    });
+
+    // Knights go home after every attack, and have to be activated again
+    for mut knight in knights.iter_mut() {
+        if let Some(knight) = &mut **knight {
+            knight.active = false;
+        }
+    }
+}
+
+/// Show how close the barbarians are, how the last attack went, and who has defended the island
+fn update_barbarian_ui(
+    mut texts: Query<&mut Text, With<BarbarianText>>,
+    mut headings: Query<(&mut Text, &PlayerHeading), Without<BarbarianText>>,
+    setup: Res<GameSetup>,
+    barbarians: Res<Barbarians>,
+) {
+    if !barbarians.is_changed() {
+        return;
+    }
+
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = match barbarians.last_attack {
+            None => format!("Barbarians: {}/{BARBARIAN_DISTANCE}", barbarians.position),
+            Some(Attack::Repelled {
+                strength,
+                defense,
+                defender,
+            }) => format!(
+                "Barbarians repelled, {defense} to {strength}{}",
+                defender.map_or(String::new(), |defender| format!(
+                    ", led by {}",
+                    setup.seat(defender).name
+                ))
+            ),
+            Some(Attack::Won {
+                strength,
+                defense,
+                cities_lost,
+            }) => format!(
+                "Barbarians won, {strength} to {defense}, and sacked {cities_lost} cit{}",
+                if cities_lost == 1 { "y" } else { "ies" }
+            ),
+        };
+    }
+
+    for (mut text, heading) in headings.iter_mut() {
+        let points = barbarians.defender_points[heading.color as usize];
+
+        text.sections[2].value = if points > 0 {
+            format!(" +{points} defender VP")
+        } else {
+            String::new()
+        };
+    }
+}
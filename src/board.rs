@@ -1,14 +1,24 @@
 use bevy::prelude::*;
+use rand::{
+    prelude::{SliceRandom, StdRng},
+    SeedableRng,
+};
 
 use crate::{
-    array::{enumerate, zip},
     building::BuildingSlot,
-    button::{BuildingButton, RoadButton},
+    button::{BuildingButton, RoadButton, TileButton},
     chit::{Chit, ChitSlot},
+    ghost::{Ghost, GHOST_COLOR},
     harbor::{Harbor, HarborSlot},
+    hex::Layout,
+    knight::KnightSlot,
+    menu::{starting_game, teardown, GameSetup},
+    overlay::{HeatSpot, OddsLabel, PipLabel, HEAT_SIZE, OVERLAY_FONT_SIZE, PIP_OFFSET},
+    preset::{BoardPresets, Terrain},
     random::Shuffle,
-    road::{RoadOrientation, RoadSlot},
+    road::RoadSlot,
     robber::RobberSlot,
+    ship::ShipSlot,
     tile::Tile,
 };
 
@@ -16,24 +26,54 @@ pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(generate_board);
+        app.init_resource::<BoardPresets>().add_system(
+            generate_board
+                .with_run_criteria(starting_game)
+                .after(teardown),
+        );
     }
 }
 
-const TILE_COUNT: usize = 19;
-const HARBOR_COUNT: usize = 30;
-const ROAD_COUNT: usize = 72;
-const BUILDING_COUNT: usize = 54;
-
 pub struct Board {
-    pub tiles: [Entity; TILE_COUNT],
-    pub chits: [Entity; TILE_COUNT],
-    pub robbers: [Entity; TILE_COUNT],
-    pub harbors: [Entity; HARBOR_COUNT],
-    pub roads: [Entity; ROAD_COUNT],
-    pub road_buttons: [Entity; ROAD_COUNT],
-    pub buildings: [Entity; BUILDING_COUNT],
-    pub building_buttons: [Entity; BUILDING_COUNT],
+    pub layout: Layout,
+    pub tiles: Vec<Entity>,
+    pub tile_buttons: Vec<Entity>,
+    pub chits: Vec<Entity>,
+    pub robbers: Vec<Entity>,
+    pub harbors: Vec<Entity>,
+    pub roads: Vec<Entity>,
+    pub road_buttons: Vec<Entity>,
+    pub ships: Vec<Entity>,
+    pub buildings: Vec<Entity>,
+    pub building_buttons: Vec<Entity>,
+    pub knights: Vec<Entity>,
+    pub ghost: Entity,
+    pub pips: Vec<Entity>,
+    pub heat: Vec<Entity>,
+    pub odds: Vec<Entity>,
+}
+
+impl Board {
+    /// Every entity that makes up the board
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.tiles
+            .iter()
+            .chain(self.tile_buttons.iter())
+            .chain(self.chits.iter())
+            .chain(self.robbers.iter())
+            .chain(self.harbors.iter())
+            .chain(self.roads.iter())
+            .chain(self.road_buttons.iter())
+            .chain(self.ships.iter())
+            .chain(self.buildings.iter())
+            .chain(self.building_buttons.iter())
+            .chain(self.knights.iter())
+            .chain(self.pips.iter())
+            .chain(self.heat.iter())
+            .chain(self.odds.iter())
+            .copied()
+            .chain([self.ghost])
+    }
 }
 
 /// Added to board items (ex. `Tile`s, `RoadSlot`s).
@@ -41,695 +81,298 @@ pub struct Board {
 #[derive(Component, Deref)]
 pub struct BoardIndex(usize);
 
-/// Maps a tile's board index to its position
-const TILE_POSITIONS: [(f32, f32); TILE_COUNT] = [
-    (-110., 190.),
-    (0., 190.),
-    (110., 190.),
-    (-165., 95.),
-    (-55., 95.),
-    (55., 95.),
-    (165., 95.),
-    (-220., 0.),
-    (-110., 0.),
-    (0., 0.),
-    (110., 0.),
-    (220., 0.),
-    (-165., -95.),
-    (-55., -95.),
-    (55., -95.),
-    (165., -95.),
-    (-110., -190.),
-    (0., -190.),
-    (110., -190.),
-];
-
-/// Maps a harbor's board index to its position
-const HARBOR_POSITIONS: [(f32, f32); HARBOR_COUNT] = [
-    (-151.25, 261.25),
-    (-68.75, 261.25),
-    (-41.25, 261.25),
-    (41.25, 261.25),
-    (68.75, 261.25),
-    (151.25, 261.25),
-    (192.5, 190.),
-    (206.25, 166.25),
-    (247.5, 95.),
-    (261.25, 71.25),
-    (302.5, 0.),
-    (261.25, -71.25),
-    (247.5, -95.),
-    (206.25, -166.25),
-    (192.5, -190.),
-    (151.25, -261.25),
-    (68.75, -261.25),
-    (41.25, -261.25),
-    (-41.25, -261.25),
-    (-68.75, -261.25),
-    (-151.25, -261.25),
-    (-192.5, -190.),
-    (-206.25, -166.25),
-    (-247.5, -95.),
-    (-261.25, -71.25),
-    (-302.5, 0.),
-    (-261.25, 71.25),
-    (-247.5, 95.),
-    (-206.25, 166.25),
-    (-192.5, 190.),
-];
-
-/// Maps a road's board index to its position
-const ROAD_POSITIONS: [(f32, f32); ROAD_COUNT] = [
-    (-137.5, 237.5),
-    (-82.5, 237.5),
-    (-27.5, 237.5),
-    (27.5, 237.5),
-    (82.5, 237.5),
-    (137.5, 237.5),
-    (-165., 190.),
-    (-55., 190.),
-    (55., 190.),
-    (165., 190.),
-    (-192.5, 142.5),
-    (-137.5, 142.5),
-    (-82.5, 142.5),
-    (-27.5, 142.5),
-    (27.5, 142.5),
-    (82.5, 142.5),
-    (137.5, 142.5),
-    (192.5, 142.5),
-    (-220., 95.),
-    (-110., 95.),
-    (0., 95.),
-    (110., 95.),
-    (220., 95.),
-    (-247.5, 47.5),
-    (-192.5, 47.5),
-    (-137.5, 47.5),
-    (-82.5, 47.5),
-    (-27.5, 47.5),
-    (27.5, 47.5),
-    (82.5, 47.5),
-    (137.5, 47.5),
-    (192.5, 47.5),
-    (247.5, 47.5),
-    (-275., 0.),
-    (-165., 0.),
-    (-55., 0.),
-    (55., 0.),
-    (165., 0.),
-    (275., 0.),
-    (-247.5, -47.5),
-    (-192.5, -47.5),
-    (-137.5, -47.5),
-    (-82.5, -47.5),
-    (-27.5, -47.5),
-    (27.5, -47.5),
-    (82.5, -47.5),
-    (137.5, -47.5),
-    (192.5, -47.5),
-    (247.5, -47.5),
-    (-220., -95.),
-    (-110., -95.),
-    (0., -95.),
-    (110., -95.),
-    (220., -95.),
-    (-192.5, -142.5),
-    (-137.5, -142.5),
-    (-82.5, -142.5),
-    (-27.5, -142.5),
-    (27.5, -142.5),
-    (82.5, -142.5),
-    (137.5, -142.5),
-    (192.5, -142.5),
-    (-165., -190.),
-    (-55., -190.),
-    (55., -190.),
-    (165., -190.),
-    (-137.5, -237.5),
-    (-82.5, -237.5),
-    (-27.5, -237.5),
-    (27.5, -237.5),
-    (82.5, -237.5),
-    (137.5, -237.5),
-];
-
-/// Maps a road's board index to its visual orientation
-pub const ROAD_ORIENTATIONS: [RoadOrientation; ROAD_COUNT] = [
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Vert,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-    RoadOrientation::Dec,
-    RoadOrientation::Inc,
-];
-
-/// Maps a road's board index to the board indices of adjacent roads
-pub const ROAD_ROAD_ADJACENCY: [&[usize]; ROAD_COUNT] = [
-    &[1, 6],
-    &[0, 2, 7],
-    &[1, 3, 7],
-    &[2, 4, 8],
-    &[3, 5, 8],
-    &[4, 9],
-    &[0, 10, 11],
-    &[1, 2, 12, 13],
-    &[3, 4, 14, 15],
-    &[5, 16, 17],
-    &[6, 11, 18],
-    &[6, 10, 12, 19],
-    &[7, 11, 13, 19],
-    &[7, 12, 14, 20],
-    &[8, 13, 15, 20],
-    &[8, 14, 16, 21],
-    &[9, 15, 17, 21],
-    &[9, 16, 22],
-    &[10, 23, 24],
-    &[11, 12, 25, 26],
-    &[13, 14, 27, 28],
-    &[15, 16, 29, 30],
-    &[17, 31, 32],
-    &[18, 24, 33],
-    &[18, 23, 25, 34],
-    &[19, 24, 26, 34],
-    &[19, 25, 27, 35],
-    &[20, 26, 28, 35],
-    &[20, 27, 29, 36],
-    &[21, 28, 30, 36],
-    &[21, 29, 31, 37],
-    &[22, 30, 32, 37],
-    &[22, 31, 38],
-    &[23, 39],
-    &[24, 25, 40, 41],
-    &[26, 27, 42, 43],
-    &[28, 29, 44, 45],
-    &[30, 31, 46, 47],
-    &[32, 48],
-    &[33, 40, 49],
-    &[34, 39, 41, 49],
-    &[34, 40, 42, 50],
-    &[35, 41, 43, 50],
-    &[35, 42, 44, 51],
-    &[36, 43, 45, 51],
-    &[36, 44, 46, 52],
-    &[37, 45, 47, 52],
-    &[37, 46, 48, 53],
-    &[38, 47, 53],
-    &[39, 40, 54],
-    &[41, 42, 55, 56],
-    &[43, 44, 57, 58],
-    &[45, 46, 59, 60],
-    &[47, 48, 61],
-    &[49, 55, 62],
-    &[50, 54, 56, 62],
-    &[50, 55, 57, 63],
-    &[51, 56, 58, 63],
-    &[51, 57, 59, 64],
-    &[52, 58, 60, 64],
-    &[52, 59, 61, 65],
-    &[53, 60, 65],
-    &[54, 55, 66],
-    &[56, 57, 67, 68],
-    &[58, 59, 69, 70],
-    &[60, 61, 71],
-    &[62, 67],
-    &[63, 66, 68],
-    &[63, 67, 69],
-    &[64, 68, 70],
-    &[64, 69, 71],
-    &[65, 70],
-];
-
-/// Maps a road's board index to the board indices of adjacent buildings
-pub const ROAD_BUILDING_ADJACENCY: [[usize; 2]; ROAD_COUNT] = [
-    [0, 1],
-    [1, 2],
-    [2, 3],
-    [3, 4],
-    [4, 5],
-    [5, 6],
-    [0, 8],
-    [2, 10],
-    [4, 12],
-    [6, 14],
-    [7, 8],
-    [8, 9],
-    [9, 10],
-    [10, 11],
-    [11, 12],
-    [12, 13],
-    [13, 14],
-    [14, 15],
-    [7, 17],
-    [9, 19],
-    [11, 21],
-    [13, 23],
-    [15, 25],
-    [16, 17],
-    [17, 18],
-    [18, 19],
-    [19, 20],
-    [20, 21],
-    [21, 22],
-    [22, 23],
-    [23, 24],
-    [24, 25],
-    [25, 26],
-    [16, 27],
-    [18, 29],
-    [20, 31],
-    [22, 33],
-    [24, 35],
-    [26, 37],
-    [27, 28],
-    [28, 29],
-    [29, 30],
-    [30, 31],
-    [31, 32],
-    [32, 33],
-    [33, 34],
-    [34, 35],
-    [35, 36],
-    [36, 37],
-    [28, 38],
-    [30, 40],
-    [32, 42],
-    [34, 44],
-    [36, 46],
-    [38, 39],
-    [39, 40],
-    [40, 41],
-    [41, 42],
-    [42, 43],
-    [43, 44],
-    [44, 45],
-    [45, 46],
-    [39, 47],
-    [41, 49],
-    [43, 51],
-    [45, 53],
-    [47, 48],
-    [48, 49],
-    [49, 50],
-    [50, 51],
-    [51, 52],
-    [52, 53],
-];
-
-/// Maps a building's board index to its position
-const BUILDING_POSITIONS: [(f32, f32); BUILDING_COUNT] = [
-    (-165., 222.),
-    (-110., 253.),
-    (-55., 222.),
-    (0., 253.),
-    (55., 222.),
-    (110., 253.),
-    (165., 222.),
-    (-220., 127.),
-    (-165., 158.),
-    (-110., 127.),
-    (-55., 158.),
-    (0., 127.),
-    (55., 158.),
-    (110., 127.),
-    (165., 158.),
-    (220., 127.),
-    (-275., 32.),
-    (-220., 63.),
-    (-165., 32.),
-    (-110., 63.),
-    (-55., 32.),
-    (0., 63.),
-    (55., 32.),
-    (110., 63.),
-    (165., 32.),
-    (220., 63.),
-    (275., 32.),
-    (-275., -32.),
-    (-220., -63.),
-    (-165., -32.),
-    (-110., -63.),
-    (-55., -32.),
-    (0., -63.),
-    (55., -32.),
-    (110., -63.),
-    (165., -32.),
-    (220., -63.),
-    (275., -32.),
-    (-220., -127.),
-    (-165., -158.),
-    (-110., -127.),
-    (-55., -158.),
-    (0., -127.),
-    (55., -158.),
-    (110., -127.),
-    (165., -158.),
-    (220., -127.),
-    (-165., -222.),
-    (-110., -253.),
-    (-55., -222.),
-    (0., -253.),
-    (55., -222.),
-    (110., -253.),
-    (165., -222.),
-];
-
-/// Maps a building's board index to the board indices of adjacent tiles
-pub const BUILDING_TILE_ADJACENCY: [&[usize]; BUILDING_COUNT] = [
-    &[0],
-    &[0],
-    &[0, 1],
-    &[1],
-    &[1, 2],
-    &[2],
-    &[2],
-    &[3],
-    &[0, 3],
-    &[0, 3, 4],
-    &[0, 1, 4],
-    &[1, 4, 5],
-    &[1, 2, 5],
-    &[2, 5, 6],
-    &[2, 6],
-    &[6],
-    &[7],
-    &[3, 7],
-    &[3, 7, 8],
-    &[3, 4, 8],
-    &[4, 8, 9],
-    &[4, 5, 9],
-    &[5, 9, 10],
-    &[5, 6, 10],
-    &[6, 10, 11],
-    &[6, 11],
-    &[11],
-    &[7],
-    &[7, 12],
-    &[7, 8, 12],
-    &[8, 12, 13],
-    &[8, 9, 13],
-    &[9, 13, 14],
-    &[9, 10, 14],
-    &[10, 14, 15],
-    &[10, 11, 15],
-    &[11, 15],
-    &[11],
-    &[12],
-    &[12, 16],
-    &[12, 13, 16],
-    &[13, 16, 17],
-    &[13, 14, 17],
-    &[14, 17, 18],
-    &[14, 15, 18],
-    &[15, 18],
-    &[15],
-    &[16],
-    &[16],
-    &[16, 17],
-    &[17],
-    &[17, 18],
-    &[18],
-    &[18],
-];
-
-/// Maps a building's board index to the board indices of adjacent roads
-pub const BUILDING_ROAD_ADJACENCY: [&[usize]; BUILDING_COUNT] = [
-    &[0, 6],
-    &[0, 1],
-    &[1, 2, 7],
-    &[2, 3],
-    &[3, 4, 8],
-    &[4, 5],
-    &[5, 9],
-    &[10, 18],
-    &[6, 10, 11],
-    &[11, 12, 19],
-    &[7, 12, 13],
-    &[13, 14, 20],
-    &[8, 14, 15],
-    &[15, 16, 21],
-    &[9, 16, 17],
-    &[17, 22],
-    &[23, 33],
-    &[18, 23, 24],
-    &[24, 25, 34],
-    &[19, 25, 26],
-    &[26, 27, 35],
-    &[20, 27, 28],
-    &[28, 29, 36],
-    &[21, 29, 30],
-    &[30, 31, 37],
-    &[22, 31, 32],
-    &[32, 38],
-    &[33, 39],
-    &[39, 40, 49],
-    &[34, 40, 41],
-    &[41, 42, 50],
-    &[35, 42, 43],
-    &[43, 44, 51],
-    &[36, 44, 45],
-    &[45, 46, 52],
-    &[37, 46, 47],
-    &[47, 48, 53],
-    &[38, 48],
-    &[49, 54],
-    &[54, 55, 62],
-    &[50, 55, 56],
-    &[56, 57, 63],
-    &[51, 57, 58],
-    &[58, 59, 64],
-    &[52, 59, 60],
-    &[60, 61, 65],
-    &[53, 61],
-    &[62, 66],
-    &[66, 67],
-    &[63, 67, 68],
-    &[68, 69],
-    &[64, 69, 70],
-    &[70, 71],
-    &[65, 71],
-];
-
-/// Maps a building's board index to the board indices of adjacent buildings
-pub const BUILDING_BUILDING_ADJACENCY: [&[usize]; BUILDING_COUNT] = [
-    &[1, 8],
-    &[0, 2],
-    &[1, 3, 7],
-    &[2, 4],
-    &[3, 5, 12],
-    &[4, 6],
-    &[5, 14],
-    &[8, 17],
-    &[0, 7, 9],
-    &[8, 10, 19],
-    &[2, 9, 11],
-    &[10, 12, 21],
-    &[4, 11, 13],
-    &[12, 14, 23],
-    &[6, 13, 15],
-    &[14, 25],
-    &[17, 27],
-    &[7, 16, 18],
-    &[17, 19, 29],
-    &[9, 18, 20],
-    &[19, 21, 31],
-    &[11, 20, 22],
-    &[21, 23, 33],
-    &[13, 22, 24],
-    &[23, 25, 35],
-    &[15, 24, 26],
-    &[25, 37],
-    &[16, 28],
-    &[27, 29, 38],
-    &[18, 28, 30],
-    &[29, 31, 40],
-    &[20, 30, 32],
-    &[31, 33, 42],
-    &[22, 32, 34],
-    &[33, 35, 44],
-    &[24, 34, 36],
-    &[35, 37, 46],
-    &[26, 36],
-    &[28, 39],
-    &[38, 40, 47],
-    &[30, 39, 41],
-    &[40, 42, 49],
-    &[32, 41, 43],
-    &[42, 44, 51],
-    &[34, 43, 45],
-    &[44, 46, 53],
-    &[36, 45],
-    &[39, 48],
-    &[47, 49],
-    &[41, 48, 50],
-    &[49, 51],
-    &[43, 50, 52],
-    &[51, 53],
-    &[45, 52],
-];
-
 const TILE_Z: f32 = 0.;
 const BUTTON_Z: f32 = 3.;
 const CHIT_Z: f32 = 1.;
 const ROBBER_Z: f32 = 2.;
-const HARBOR_Z: f32 = 0.;
+// Harbors are drawn over the sea
+const HARBOR_Z: f32 = 1.;
 const ROAD_Z: f32 = 1.;
+const SHIP_Z: f32 = 1.;
 const BUILDING_Z: f32 = 1.;
+const KNIGHT_Z: f32 = 1.;
+// The ghost is drawn over the button it's previewing
+const GHOST_Z: f32 = 4.;
+// The heat map tints the tiles, under the pieces
+const HEAT_Z: f32 = 0.5;
+const OVERLAY_Z: f32 = 5.;
+
+fn generate_board(
+    mut commands: Commands,
+    setup: Res<GameSetup>,
+    presets: Res<BoardPresets>,
+    assets: Res<AssetServer>,
+) {
+    // A seed lets a group replay a board they liked
+    let mut rng = match setup.seed() {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let preset = presets.get(&setup.board);
+    let layout = Layout::new(&preset.hexes, preset.harbor_edges().as_deref());
+
+    // Deal the bags out to the board, or pick at random if the board doesn't have them
+    let land_count = preset
+        .hexes
+        .iter()
+        .filter(|(_, terrain)| *terrain == Terrain::Land)
+        .count();
+    let mut tile_bag = match &preset.tiles {
+        Some(tiles) => tiles.clone(),
+        None => Tile::shuffle(land_count, &mut rng),
+    };
+    tile_bag.shuffle(&mut rng);
+    let mut tile_bag = tile_bag.into_iter();
+    let tiles: Vec<_> = preset
+        .hexes
+        .iter()
+        .map(|(_, terrain)| match terrain {
+            Terrain::Land => tile_bag.next().unwrap(),
+            Terrain::Desert => Tile::Desert,
+            Terrain::Sea => Tile::Sea,
+        })
+        .collect();
 
-fn generate_board(mut commands: Commands) {
-    let tiles = Tile::shuffle();
+    let chit_count = tiles.iter().filter(|tile| tile.effect().is_some()).count();
+    let mut chit_bag = match &preset.chits {
+        Some(chits) => chits.clone(),
+        None => Chit::shuffle(chit_count, &mut rng),
+    };
+    chit_bag.shuffle(&mut rng);
+    let mut chit_bag = chit_bag.into_iter();
+
+    let mut harbor_bag = match &preset.harbor_bag {
+        Some(harbors) => harbors.iter().copied().map(Some).collect(),
+        None => Option::<Harbor>::shuffle(layout.harbor_positions.len(), &mut rng),
+    };
+    harbor_bag.shuffle(&mut rng);
 
     // The `enumerate` calls here are used to generate board indices
     let board = Board {
-        tiles: enumerate(tiles).map(|(i, tile)| {
-            commands
-                .spawn()
-                .insert(tile)
-                .insert(BoardIndex(i))
-                .insert(Transform::from_translation(
-                    Vec2::from(TILE_POSITIONS[i]).extend(TILE_Z),
-                ))
-                .id()
-        }),
-        chits: enumerate(zip(Chit::shuffle(), tiles)).map(|(i, (chit, tile))| {
-            commands
-                .spawn()
-                .insert(ChitSlot((!tile.robber_home()).then(|| chit)))
-                .insert(BoardIndex(i))
-                .insert(Transform::from_translation(
-                    Vec2::from(TILE_POSITIONS[i]).extend(CHIT_Z),
-                ))
-                .id()
-        }),
-        robbers: enumerate(tiles).map(|(i, tile)| {
-            commands
-                .spawn()
-                .insert(RobberSlot(tile.robber_home()))
-                .insert(BoardIndex(i))
-                .insert(Transform::from_translation(
-                    Vec2::from(TILE_POSITIONS[i]).extend(ROBBER_Z),
-                ))
-                .id()
-        }),
-        harbors: enumerate(Option::<Harbor>::shuffle()).map(|(i, harbor)| {
-            commands
-                .spawn()
-                .insert(HarborSlot(harbor))
-                .insert(BoardIndex(i))
-                .insert(Transform::from_translation(
-                    Vec2::from(HARBOR_POSITIONS[i]).extend(HARBOR_Z),
-                ))
-                .id()
-        }),
-        roads: enumerate([(); ROAD_COUNT]).map(|(i, _)| {
-            commands
-                .spawn()
-                .insert(RoadSlot(None))
-                .insert(BoardIndex(i))
-                .insert(Transform::from_translation(
-                    Vec2::from(ROAD_POSITIONS[i]).extend(ROAD_Z),
-                ))
-                .id()
-        }),
-        road_buttons: enumerate([(); ROAD_COUNT]).map(|(i, _)| {
-            commands
-                .spawn()
-                .insert(RoadButton)
-                .insert(BoardIndex(i))
-                .insert(Transform::from_translation(
-                    Vec2::from(ROAD_POSITIONS[i]).extend(BUTTON_Z),
-                ))
-                .insert(Visibility { is_visible: false })
-                .id()
-        }),
-        buildings: enumerate([(); BUILDING_COUNT]).map(|(i, _)| {
-            commands
-                .spawn()
-                .insert(BuildingSlot(None))
-                .insert(BoardIndex(i))
-                .insert(Transform::from_translation(
-                    Vec2::from(BUILDING_POSITIONS[i]).extend(BUILDING_Z),
-                ))
-                .id()
-        }),
-        building_buttons: enumerate([(); BUILDING_COUNT]).map(|(i, _)| {
-            commands
-                .spawn()
-                .insert(BuildingButton)
-                .insert(BoardIndex(i))
-                .insert(Transform::from_translation(
-                    Vec2::from(BUILDING_POSITIONS[i]).extend(BUTTON_Z),
-                ))
-                .insert(Visibility { is_visible: false })
-                .id()
-        }),
+        tiles: tiles
+            .iter()
+            .enumerate()
+            .map(|(i, tile)| {
+                commands
+                    .spawn()
+                    .insert(*tile)
+                    .insert(BoardIndex(i))
+                    .insert(Transform::from_translation(
+                        layout.tile_positions[i].extend(TILE_Z),
+                    ))
+                    .id()
+            })
+            .collect(),
+        tile_buttons: (0..tiles.len())
+            .map(|i| {
+                commands
+                    .spawn()
+                    .insert(TileButton)
+                    .insert(BoardIndex(i))
+                    .insert(Transform::from_translation(
+                        layout.tile_positions[i].extend(BUTTON_Z),
+                    ))
+                    .insert(Visibility { is_visible: false })
+                    .id()
+            })
+            .collect(),
+        chits: tiles
+            .iter()
+            .enumerate()
+            .map(|(i, tile)| {
+                commands
+                    .spawn()
+                    .insert(ChitSlot(tile.effect().and_then(|_| chit_bag.next())))
+                    .insert(BoardIndex(i))
+                    .insert(Transform::from_translation(
+                        layout.tile_positions[i].extend(CHIT_Z),
+                    ))
+                    .id()
+            })
+            .collect(),
+        robbers: tiles
+            .iter()
+            .enumerate()
+            .map(|(i, tile)| {
+                commands
+                    .spawn()
+                    .insert(RobberSlot(match preset.robber {
+                        Some(robber) => preset.hexes[i].0 == robber,
+                        None => tile.robber_home(),
+                    }))
+                    .insert(BoardIndex(i))
+                    .insert(Transform::from_translation(
+                        layout.tile_positions[i].extend(ROBBER_Z),
+                    ))
+                    .id()
+            })
+            .collect(),
+        harbors: harbor_bag
+            .into_iter()
+            .enumerate()
+            .map(|(i, harbor)| {
+                commands
+                    .spawn()
+                    .insert(HarborSlot(harbor))
+                    .insert(BoardIndex(i))
+                    .insert(Transform::from_translation(
+                        layout.harbor_positions[i].extend(HARBOR_Z),
+                    ))
+                    .id()
+            })
+            .collect(),
+        roads: (0..layout.road_positions.len())
+            .map(|i| {
+                commands
+                    .spawn()
+                    .insert(RoadSlot(None))
+                    .insert(BoardIndex(i))
+                    .insert(Transform::from_translation(
+                        layout.road_positions[i].extend(ROAD_Z),
+                    ))
+                    .id()
+            })
+            .collect(),
+        road_buttons: (0..layout.road_positions.len())
+            .map(|i| {
+                commands
+                    .spawn()
+                    .insert(RoadButton)
+                    .insert(BoardIndex(i))
+                    .insert(Transform::from_translation(
+                        layout.road_positions[i].extend(BUTTON_Z),
+                    ))
+                    .insert(Visibility { is_visible: false })
+                    .id()
+            })
+            .collect(),
+        ships: (0..layout.road_positions.len())
+            .map(|i| {
+                commands
+                    .spawn()
+                    .insert(ShipSlot(None))
+                    .insert(BoardIndex(i))
+                    .insert(Transform::from_translation(
+                        layout.road_positions[i].extend(SHIP_Z),
+                    ))
+                    .id()
+            })
+            .collect(),
+        buildings: (0..layout.building_positions.len())
+            .map(|i| {
+                commands
+                    .spawn()
+                    .insert(BuildingSlot(None))
+                    .insert(BoardIndex(i))
+                    .insert(Transform::from_translation(
+                        layout.building_positions[i].extend(BUILDING_Z),
+                    ))
+                    .id()
+            })
+            .collect(),
+        building_buttons: (0..layout.building_positions.len())
+            .map(|i| {
+                commands
+                    .spawn()
+                    .insert(BuildingButton)
+                    .insert(BoardIndex(i))
+                    .insert(Transform::from_translation(
+                        layout.building_positions[i].extend(BUTTON_Z),
+                    ))
+                    .insert(Visibility { is_visible: false })
+                    .id()
+            })
+            .collect(),
+        knights: (0..layout.building_positions.len())
+            .map(|i| {
+                commands
+                    .spawn()
+                    .insert(KnightSlot(None))
+                    .insert(BoardIndex(i))
+                    .insert(Transform::from_translation(
+                        layout.building_positions[i].extend(KNIGHT_Z),
+                    ))
+                    .id()
+            })
+            .collect(),
+        ghost: commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: GHOST_COLOR,
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::Z * GHOST_Z),
+                visibility: Visibility { is_visible: false },
+                ..default()
+            })
+            .insert(Ghost)
+            .id(),
+        pips: (0..tiles.len())
+            .map(|i| {
+                commands
+                    .spawn_bundle(overlay_text(
+                        (layout.tile_positions[i] - Vec2::Y * PIP_OFFSET).extend(OVERLAY_Z),
+                        &assets,
+                    ))
+                    .insert(PipLabel)
+                    .insert(BoardIndex(i))
+                    .id()
+            })
+            .collect(),
+        heat: (0..layout.building_positions.len())
+            .map(|i| {
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(HEAT_SIZE)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(
+                            layout.building_positions[i].extend(HEAT_Z),
+                        ),
+                        visibility: Visibility { is_visible: false },
+                        ..default()
+                    })
+                    .insert(HeatSpot)
+                    .insert(BoardIndex(i))
+                    .id()
+            })
+            .collect(),
+        odds: (0..layout.building_positions.len())
+            .map(|i| {
+                commands
+                    .spawn_bundle(overlay_text(
+                        layout.building_positions[i].extend(OVERLAY_Z),
+                        &assets,
+                    ))
+                    .insert(OddsLabel)
+                    .insert(BoardIndex(i))
+                    .id()
+            })
+            .collect(),
+        layout,
     };
 
     commands.insert_resource(board);
 }
+
+/// Hidden, centered text for the overlay, which fills it in
+fn overlay_text(translation: Vec3, assets: &AssetServer) -> Text2dBundle {
+    Text2dBundle {
+        text: Text::with_section(
+            "",
+            TextStyle {
+                font: assets.load("FiraSans-Bold.ttf"),
+                font_size: OVERLAY_FONT_SIZE,
+                color: Color::WHITE,
+            },
+            TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Center,
+            },
+        ),
+        transform: Transform::from_translation(translation),
+        visibility: Visibility { is_visible: false },
+        ..default()
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use rand::{prelude::SliceRandom, thread_rng};
+
+use crate::{
+    button::{BuildingButton, Clicked, RoadButton, TileButton},
+    discard::DiscardButton,
+    draft::DraftButton,
+    gold::ChoiceButton,
+    improvement::ImprovementButton,
+    menu::{in_game, GameSetup, SetupMode},
+    turn::{Players, Turn},
+};
+
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(play_bots.with_run_criteria(in_game));
+    }
+}
+
+/// How long a bot waits before each move, in seconds, so the humans can follow along
+const BOT_DELAY: f32 = 0.5;
+
+/// Take turns for the players that are bots.
+/// They aren't very smart: they build wherever they're shown a button, and otherwise end their turn.
+fn play_bots(
+    mut commands: Commands,
+    building_buttons: Query<(Entity, &Visibility), With<BuildingButton>>,
+    road_buttons: Query<(Entity, &Visibility), With<RoadButton>>,
+    tile_buttons: Query<(Entity, &Visibility), With<TileButton>>,
+    choice_buttons: Query<Entity, With<ChoiceButton>>,
+    discard_buttons: Query<Entity, With<DiscardButton>>,
+    improvement_buttons: Query<Entity, With<ImprovementButton>>,
+    draft_buttons: Query<Entity, With<DraftButton>>,
+    setup: Res<GameSetup>,
+    players: Res<Players>,
+    time: Res<Time>,
+    mut turn: ResMut<Turn>,
+    mut waited: Local<f32>,
+) {
+    let player = match turn.player() {
+        Some(player) => player,
+        None => return,
+    };
+
+    // In the random setup mode, the game places everyone's starting pieces like it would for a bot
+    let random_setup = setup.setup_mode == SetupMode::Random && matches!(*turn, Turn::Setup { .. });
+
+    if !setup.seat(players[player]).bot && !random_setup {
+        *waited = 0.;
+        return;
+    }
+
+    *waited += time.delta_seconds();
+    if *waited < BOT_DELAY {
+        return;
+    }
+    *waited = 0.;
+
+    match *turn {
+        Turn::Setup { road: false, .. }
+        | Turn::BuildSettlement { .. }
+        | Turn::BuildCity { .. }
+        | Turn::Knight { .. }
+        | Turn::Steal { .. } => click_random(&mut commands, building_buttons.iter()),
+        Turn::Setup { road: true, .. }
+        | Turn::BuildRoad { .. }
+        | Turn::BuildShip { .. }
+        | Turn::MoveShip { .. } => click_random(&mut commands, road_buttons.iter()),
+        Turn::MoveRobber { .. } => click_random(&mut commands, tile_buttons.iter()),
+        Turn::Roll { .. } | Turn::Build { .. } => *turn = turn.next(players.len()),
+        // Bots pick a random resource
+        Turn::ChooseResource { .. } => press_random(&mut commands, choice_buttons.iter()),
+        // Bots discard at random
+        Turn::Discard { .. } => press_random(&mut commands, discard_buttons.iter()),
+        // Bots pick a random place in the turn order
+        Turn::Draft { .. } => press_random(&mut commands, draft_buttons.iter()),
+        // Bots pick a random track
+        Turn::ImproveCity { .. } => press_random(&mut commands, improvement_buttons.iter()),
+        Turn::Production { .. } | Turn::Done => (),
+    }
+}
+
+/// Click a random visible button
+fn click_random<'a>(
+    commands: &mut Commands,
+    buttons: impl Iterator<Item = (Entity, &'a Visibility)>,
+) {
+    let visible: Vec<_> = buttons
+        .filter(|(_, visibility)| visibility.is_visible)
+        .map(|(entity, _)| entity)
+        .collect();
+
+    if let Some(&button) = visible.choose(&mut thread_rng()) {
+        commands.entity(button).insert(Clicked);
+    }
+}
+
+/// Press a random UI button. Only the buttons for the current choice are spawned, so they're all fair game.
+fn press_random(commands: &mut Commands, buttons: impl Iterator<Item = Entity>) {
+    let buttons: Vec<_> = buttons.collect();
+
+    if let Some(&button) = buttons.choose(&mut thread_rng()) {
+        commands.entity(button).insert(Clicked);
+    }
+}
@@ -1,30 +1,39 @@
 use bevy::prelude::*;
 
 use crate::{
-    board::{
-        Board, BoardIndex, BUILDING_BUILDING_ADJACENCY, BUILDING_ROAD_ADJACENCY,
-        BUILDING_TILE_ADJACENCY,
-    },
+    board::{Board, BoardIndex},
     button::{BuildingButton, Clicked},
     color::PlayerColor,
     image::UpdateImages,
-    resource::{Hands, Resource},
+    island::HomeIslands,
+    knight::KnightSlot,
+    menu::{in_game, GameSetup, Ruleset},
+    resource::Hands,
     road::RoadSlot,
-    tile::Tile,
+    rules::Rules,
+    ship::ShipSlot,
+    tile::{Tile, TileEffect},
     turn::{Players, Turn},
-    ui::BuildSettlementButton,
+    ui::{BuildCityButton, BuildSettlementButton},
+    undo::{Action, Placement, UndoStack},
 };
 
 pub struct BuildingPlugin;
 
 impl Plugin for BuildingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(show_building_buttons)
-            .add_system(build_settlement);
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(show_building_buttons)
+                .with_system(build_settlement)
+                .with_system(show_city_buttons)
+                .with_system(build_city),
+        );
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum BuildingType {
     Settlement,
     City,
@@ -50,7 +59,7 @@ pub struct Building {
 pub struct BuildingSlot(pub Option<Building>);
 
 impl UpdateImages for BuildingSlot {
-    fn image(self, _: usize) -> Option<&'static str> {
+    fn image(self, _: &Board, _: usize) -> Option<&'static str> {
         match *self {
             None => None,
             Some(Building {
@@ -61,6 +70,8 @@ impl UpdateImages for BuildingSlot {
                 PlayerColor::Orange => "orange_settlement.png",
                 PlayerColor::Red => "red_settlement.png",
                 PlayerColor::White => "white_settlement.png",
+                PlayerColor::Green => "green_settlement.png",
+                PlayerColor::Brown => "brown_settlement.png",
             }),
             Some(Building {
                 building_type: BuildingType::City,
@@ -70,11 +81,72 @@ impl UpdateImages for BuildingSlot {
                 PlayerColor::Orange => "orange_city.png",
                 PlayerColor::Red => "red_city.png",
                 PlayerColor::White => "white_city.png",
+                PlayerColor::Green => "green_city.png",
+                PlayerColor::Brown => "brown_city.png",
             }),
         }
     }
 }
 
+/// How many buildings of this type the player has on the board
+pub fn building_count(
+    color: PlayerColor,
+    building_type: BuildingType,
+    buildings: &Query<&BuildingSlot>,
+) -> usize {
+    buildings
+        .iter()
+        .filter(|building| {
+            matches!(***building, Some(building) if building.color == color && building.building_type == building_type)
+        })
+        .count()
+}
+
+/// Whether the player may build a settlement here: it's on land, it's next to that player's road or ship,
+/// there are no buildings or knights here, and there are no adjacent buildings.
+/// The second criterion is relaxed in the setup phase.
+pub fn can_build_settlement(
+    index: usize,
+    color: PlayerColor,
+    setup: bool,
+    board: &Board,
+    buildings: &Query<&BuildingSlot>,
+    roads: &Query<&RoadSlot>,
+    ships: &Query<&ShipSlot>,
+    knights: &Query<&KnightSlot>,
+) -> bool {
+    board.layout.building_islands[index].is_some()
+        && (setup
+            || board.layout.building_roads[index].iter().any(|road| {
+                roads
+                    .get(board.roads[*road])
+                    .unwrap()
+                    .map_or(false, |road| color == road.color)
+                    || matches!(**ships.get(board.ships[*road]).unwrap(), Some(ship) if ship.color == color)
+            }))
+        && buildings.get(board.buildings[index]).unwrap().is_none()
+        && knights.get(board.knights[index]).unwrap().is_none()
+        && board.layout.building_buildings[index]
+            .iter()
+            .all(|building| buildings.get(board.buildings[*building]).unwrap().is_none())
+}
+
+/// Whether the player has a settlement here to upgrade to a city
+pub fn can_build_city(
+    index: usize,
+    color: PlayerColor,
+    board: &Board,
+    buildings: &Query<&BuildingSlot>,
+) -> bool {
+    matches!(
+        **buildings.get(board.buildings[index]).unwrap(),
+        Some(Building {
+            building_type: BuildingType::Settlement,
+            color: other,
+        }) if other == color
+    )
+}
+
 /// Show the buttons that appear when building settlements or cities
 pub fn show_building_buttons(
     mut buttons: Query<(&mut Visibility, &BoardIndex), With<BuildingButton>>,
@@ -82,74 +154,61 @@ pub fn show_building_buttons(
     build_buttons: Query<&Interaction, (With<BuildSettlementButton>, Changed<Interaction>)>,
     buildings: Query<&BuildingSlot>,
     roads: Query<&RoadSlot>,
+    ships: Query<&ShipSlot>,
+    knights: Query<&KnightSlot>,
     board: Res<Board>,
     players: Res<Players>,
-    mut hands: ResMut<Hands>,
+    hands: Res<Hands>,
+    rules: Res<Rules>,
     mut turn: ResMut<Turn>,
 ) {
-    if let Some((player, setup)) = match *turn {
+    if let Some((player, special, setup)) = match *turn {
         // We're in a settlement-building phase of a setup round
         // Show the buttons if we've just entered the phase
         Turn::Setup {
             player,
             road: false,
             ..
-        } => turn.is_changed().then(|| (player, true)),
+        } => turn.is_changed().then(|| (player, None, true)),
         // We're in a build phase
-        // Show the buttons if the player pressed the button to build, and has enough resources
-        Turn::Build { player } => build_buttons.get_single().ok().and_then(|interaction| {
-            if let Interaction::Clicked = interaction {
-                let hand = hands[players[player] as usize];
-
-                (hand[Resource::Brick as usize] >= 1
-                    && hand[Resource::Wool as usize] >= 1
-                    && hand[Resource::Grain as usize] >= 1
-                    && hand[Resource::Lumber as usize] >= 1)
-                    .then(|| (player, false))
-            } else {
-                None
-            }
-        }),
+        // Show the buttons if the player pressed the button to build, has enough resources,
+        // and has a settlement left to build
+        Turn::Build { player, special } => {
+            build_buttons.get_single().ok().and_then(|interaction| {
+                if let Interaction::Clicked = interaction {
+                    let color = players[player];
+
+                    (rules.settlement_cost.can_afford(hands[color as usize])
+                        && building_count(color, BuildingType::Settlement, &buildings)
+                            < rules.max_settlements)
+                        .then(|| (player, special, false))
+                } else {
+                    None
+                }
+            })
+        }
         _ => None,
     } {
         let mut can_build = false;
         let color = players[player];
 
         for (mut visibility, index) in buttons.iter_mut() {
-            // The player may build a settlement here if it's next to that player's road,
-            // there are no buildings here, and there are no adjacent roads.
-            // The first criterion is relaxed in the setup phase.
-            let visible = (setup
-                || BUILDING_ROAD_ADJACENCY[**index].iter().any(|road| {
-                    roads
-                        .get(board.roads[*road])
-                        .unwrap()
-                        .map_or(false, |road| color == road.color)
-                }))
-                && buildings.get(board.buildings[**index]).unwrap().is_none()
-                && BUILDING_BUILDING_ADJACENCY[**index]
-                    .iter()
-                    .all(|building| buildings.get(board.buildings[*building]).unwrap().is_none());
+            let visible = can_build_settlement(
+                **index, color, setup, &board, &buildings, &roads, &ships, &knights,
+            );
 
             visibility.is_visible = visible;
             can_build |= visible;
         }
 
-        // If they aren't in setup, they should be charged for the build
+        // If they aren't in setup, they're charged once they pick a spot
         if can_build && !setup {
-            let hand = &mut hands[color as usize];
-
-            hand[Resource::Brick as usize] -= 1;
-            hand[Resource::Wool as usize] -= 1;
-            hand[Resource::Grain as usize] -= 1;
-            hand[Resource::Lumber as usize] -= 1;
-
-            *turn = Turn::BuildSettlement { player };
+            *turn = Turn::BuildSettlement { player, special };
         }
     }
 }
 
-/// Build a settlement
+/// Build a settlement. In Cities & Knights, the second setup round builds a city instead.
 fn build_settlement(
     mut commands: Commands,
     clicked_buttons: Query<(Entity, &BoardIndex), (With<BuildingButton>, With<Clicked>)>,
@@ -158,18 +217,23 @@ fn build_settlement(
     tiles: Query<&Tile>,
     board: Res<Board>,
     players: Res<Players>,
+    game_setup: Res<GameSetup>,
+    rules: Res<Rules>,
     mut turn: ResMut<Turn>,
     mut hands: ResMut<Hands>,
+    mut undo_stack: ResMut<UndoStack>,
+    mut home_islands: ResMut<HomeIslands>,
 ) {
-    if let Some((round_2, player)) = match *turn {
+    if let Some((round, player, setup)) = match *turn {
         // We're in a settlement-building phase of a setup round
         Turn::Setup {
-            round_2,
+            round,
             player,
             road: false,
-        } => Some((round_2, player)),
+            ..
+        } => Some((round, player, true)),
         // We're building a settlement because the player pressed the Build settlement button
-        Turn::BuildSettlement { player } => Some((false, player)),
+        Turn::BuildSettlement { player, .. } => Some((0, player, false)),
         _ => None,
     } {
         for (entity, index) in clicked_buttons.iter() {
@@ -177,16 +241,47 @@ fn build_settlement(
 
             commands.entity(entity).remove::<Clicked>();
 
+            // Settlements are free in the setup phase, and can't be taken back
+            if !setup {
+                let hand = &mut hands[color as usize];
+
+                undo_stack.push(Action {
+                    color,
+                    hand: *hand,
+                    placement: Some(Placement::Building {
+                        index: **index,
+                        previous: **buildings.get(board.buildings[**index]).unwrap(),
+                    }),
+                });
+
+                rules.settlement_cost.pay(hand);
+            }
+
             **buildings.get_mut(board.buildings[**index]).unwrap() = Some(Building {
-                building_type: BuildingType::Settlement,
+                building_type: if round == 2 && game_setup.ruleset == Ruleset::CitiesAndKnights {
+                    BuildingType::City
+                } else {
+                    BuildingType::Settlement
+                },
                 color,
             });
 
+            // Players start out at home on the islands they settle in the setup phase
+            if let (true, Some(island)) = (setup, board.layout.building_islands[**index]) {
+                let home_islands = &mut home_islands[color as usize];
+
+                if !home_islands.contains(&island) {
+                    home_islands.push(island);
+                }
+            }
+
             // In round 2 of setup, the player is given resources based on the tiles they started adjacent to
-            if round_2 {
+            if round == 2 {
                 let hand = &mut hands[color as usize];
-                for tile in BUILDING_TILE_ADJACENCY[**index] {
-                    if let Some(resource) = tiles.get(board.tiles[*tile]).unwrap().resource() {
+                for tile in &board.layout.building_tiles[**index] {
+                    if let Some(TileEffect::Produce(resource)) =
+                        tiles.get(board.tiles[*tile]).unwrap().effect()
+                    {
                         hand[resource as usize] += 1;
                     }
                 }
@@ -196,7 +291,96 @@ fn build_settlement(
                 visibility.is_visible = false;
             }
 
-            *turn = turn.next();
+            *turn = turn.next(players.len());
+        }
+    }
+}
+
+/// Show the buttons on the player's settlements when they press the button to build a city,
+/// if they have enough resources, and a city left to build.
+/// Cities are part of every ruleset, but Cities & Knights can't be played without them,
+/// since only cities make commodities, and city improvements need one.
+fn show_city_buttons(
+    mut buttons: Query<(&mut Visibility, &BoardIndex), With<BuildingButton>>,
+    build_buttons: Query<&Interaction, (With<BuildCityButton>, Changed<Interaction>)>,
+    buildings: Query<&BuildingSlot>,
+    board: Res<Board>,
+    players: Res<Players>,
+    hands: Res<Hands>,
+    rules: Res<Rules>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::Build { player, special } = *turn {
+        for interaction in build_buttons.iter() {
+            if let Interaction::Clicked = interaction {
+                let color = players[player];
+
+                if !rules.city_cost.can_afford(hands[color as usize])
+                    || building_count(color, BuildingType::City, &buildings) >= rules.max_cities
+                {
+                    continue;
+                }
+
+                let mut can_build = false;
+
+                for (mut visibility, index) in buttons.iter_mut() {
+                    let visible = can_build_city(**index, color, &board, &buildings);
+
+                    visibility.is_visible = visible;
+                    can_build |= visible;
+                }
+
+                // They're charged once they pick a settlement
+                if can_build {
+                    *turn = Turn::BuildCity { player, special };
+                }
+            }
+        }
+    }
+}
+
+/// Upgrade a settlement to a city
+fn build_city(
+    mut commands: Commands,
+    clicked_buttons: Query<(Entity, &BoardIndex), (With<BuildingButton>, With<Clicked>)>,
+    mut buttons: Query<&mut Visibility, With<BuildingButton>>,
+    mut buildings: Query<&mut BuildingSlot>,
+    board: Res<Board>,
+    players: Res<Players>,
+    rules: Res<Rules>,
+    mut turn: ResMut<Turn>,
+    mut hands: ResMut<Hands>,
+    mut undo_stack: ResMut<UndoStack>,
+) {
+    if let Turn::BuildCity { player, .. } = *turn {
+        for (entity, index) in clicked_buttons.iter() {
+            commands.entity(entity).remove::<Clicked>();
+
+            let color = players[player];
+            let hand = &mut hands[color as usize];
+            let mut building = buildings.get_mut(board.buildings[**index]).unwrap();
+
+            undo_stack.push(Action {
+                color,
+                hand: *hand,
+                placement: Some(Placement::Building {
+                    index: **index,
+                    previous: **building,
+                }),
+            });
+
+            rules.city_cost.pay(hand);
+
+            **building = Some(Building {
+                building_type: BuildingType::City,
+                color,
+            });
+
+            for mut visibility in buttons.iter_mut() {
+                visibility.is_visible = false;
+            }
+
+            *turn = turn.next(players.len());
         }
     }
 }
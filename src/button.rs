@@ -1,16 +1,31 @@
 use bevy::prelude::*;
 
-use crate::{cursor::CursorPosition, image::ButtonImage, math::RectContains};
+use crate::{
+    board::{Board, BoardIndex},
+    cursor::CursorPosition,
+    hot_seat::PassTo,
+    image::ButtonImage,
+    math::RectContains,
+    menu::in_game,
+    road::RoadOrientation,
+};
 
 pub struct ButtonPlugin;
 
 impl Plugin for ButtonPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(press_button);
+        app.init_resource::<HoveredButton>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(hover_button)
+                .with_system(highlight_buttons.after(hover_button))
+                .with_system(press_button.after(hover_button)),
+        );
     }
 }
 
-/// Buttons that appear on the nodes of the board, for building settlements and cities
+/// Buttons that appear on the nodes of the board, for building settlements and cities,
+/// and picking a player to steal from
 #[derive(Component)]
 pub struct BuildingButton;
 
@@ -20,7 +35,7 @@ impl ButtonImage for BuildingButton {
     }
 }
 
-/// Buttons that appear on the edges of the board, for building roads
+/// Buttons that appear on the edges of the board, for building roads and ships, and moving ships
 #[derive(Component)]
 pub struct RoadButton;
 
@@ -30,68 +45,163 @@ impl ButtonImage for RoadButton {
     }
 }
 
-/// Used on non-UI buttons
+/// Buttons that appear on the tiles of the board, for moving the robber
+#[derive(Component)]
+pub struct TileButton;
+
+impl ButtonImage for TileButton {
+    fn image() -> &'static str {
+        "building_button.png"
+    }
+}
+
+/// Used on non-UI buttons, and on UI buttons that bots press, since `Interaction` belongs to Bevy
 #[derive(Component)]
 #[component(storage = "SparseSet")]
 pub struct Clicked;
 
+/// UI buttons that might have just been pressed, by a player or a bot. Check them with `pressed`.
+pub type MaybePressed = Or<(Changed<Interaction>, With<Clicked>)>;
+
+/// Whether a UI button was pressed, by a player clicking it, or a bot giving it `Clicked`.
+/// `Clicked` is removed, so the button is only pressed once.
+pub fn pressed(
+    commands: &mut Commands,
+    button: Entity,
+    interaction: &Interaction,
+    clicked: Option<&Clicked>,
+) -> bool {
+    if clicked.is_some() {
+        commands.entity(button).remove::<Clicked>();
+        return true;
+    }
+
+    matches!(interaction, Interaction::Clicked)
+}
+
+/// The visible button under the cursor, if any
+#[derive(Default, Deref, DerefMut)]
+pub struct HoveredButton(Option<Entity>);
+
+#[derive(Clone, Copy)]
 pub enum ButtonType {
     Building,
-    Road,
+    Road(RoadOrientation),
+    Tile,
 }
 
-// These are radii for, uh, squares
 const BUILDING_BUTTON_RADIUS: f32 = 16.;
-const ROAD_BUTTON_RADIUS: f32 = 16.;
+/// Road buttons are rectangles along the road
+const ROAD_BUTTON_HALF_LENGTH: f32 = 24.;
+const ROAD_BUTTON_HALF_WIDTH: f32 = 10.;
+const TILE_BUTTON_RADIUS: f32 = 32.;
+const HOVERED_BUTTON_COLOR: Color = Color::rgb(1., 1., 0.6);
+const PRESSED_BUTTON_COLOR: Color = Color::rgb(0.7, 0.7, 0.4);
 
 impl ButtonType {
-    fn radius(self) -> f32 {
+    /// Whether the button covers a point, given relative to the button's center
+    fn contains(self, offset: Vec2) -> bool {
         match self {
-            ButtonType::Building => BUILDING_BUTTON_RADIUS,
-            ButtonType::Road => ROAD_BUTTON_RADIUS,
+            ButtonType::Building => offset.length() < BUILDING_BUTTON_RADIUS,
+            ButtonType::Road(orientation) => {
+                // Turn the point so the road runs left to right
+                let direction = orientation.direction();
+                let offset = Vec2::new(offset.dot(direction), direction.perp_dot(offset));
+
+                Rect {
+                    left: -ROAD_BUTTON_HALF_LENGTH,
+                    right: ROAD_BUTTON_HALF_LENGTH,
+                    top: ROAD_BUTTON_HALF_WIDTH,
+                    bottom: -ROAD_BUTTON_HALF_WIDTH,
+                }
+                .contains(offset)
+            }
+            ButtonType::Tile => offset.length() < TILE_BUTTON_RADIUS,
         }
     }
 }
 
-/// Add `Clicked` component to buttons that were just clicked
-fn press_button(
-    mut commands: Commands,
+/// Find the button under the cursor. Where buttons overlap, the one with the nearest center wins.
+fn hover_button(
     buttons: Query<
         (
             Entity,
             Option<&BuildingButton>,
             Option<&RoadButton>,
+            &BoardIndex,
             &Transform,
             &Visibility,
         ),
-        Or<(With<BuildingButton>, With<RoadButton>)>,
+        Or<(With<BuildingButton>, With<RoadButton>, With<TileButton>)>,
     >,
+    board: Res<Board>,
     cursor_position: Res<CursorPosition>,
+    pass_to: Res<PassTo>,
+    mut hovered: ResMut<HoveredButton>,
+) {
+    // The board is hidden while the device is being passed
+    let new_hovered = cursor_position
+        .filter(|_| pass_to.is_none())
+        .and_then(|cursor_position| {
+            buttons
+                .iter()
+                .filter(|(.., visibility)| visibility.is_visible)
+                .filter_map(
+                    |(entity, building_button, road_button, index, transform, _)| {
+                        let button_type = match (building_button, road_button) {
+                            (Some(_), _) => ButtonType::Building,
+                            (_, Some(_)) => {
+                                ButtonType::Road(board.layout.road_orientations[**index])
+                            }
+                            _ => ButtonType::Tile,
+                        };
+                        let offset = cursor_position - transform.translation.truncate();
+
+                        button_type
+                            .contains(offset)
+                            .then(|| (entity, offset.length()))
+                    },
+                )
+                .min_by(|(_, distance), (_, other)| distance.total_cmp(other))
+                .map(|(entity, _)| entity)
+        });
+
+    if **hovered != new_hovered {
+        **hovered = new_hovered;
+    }
+}
+
+/// Light up the button under the cursor, and darken it while it's held
+fn highlight_buttons(
+    mut buttons: Query<
+        (Entity, &mut Sprite),
+        Or<(With<BuildingButton>, With<RoadButton>, With<TileButton>)>,
+    >,
+    hovered: Res<HoveredButton>,
     mouse: Res<Input<MouseButton>>,
 ) {
-    if let Some(cursor_position) = **cursor_position {
-        if mouse.just_pressed(MouseButton::Left) {
-            for (entity, building_button, road_button, transform, visibility) in buttons.iter() {
-                if visibility.is_visible {
-                    // Figure out what button type we're using, and get the radius
-                    let radius = building_button
-                        .map(|_| ButtonType::Building)
-                        .unwrap_or_else(|| road_button.map(|_| ButtonType::Road).unwrap())
-                        .radius();
-                    let translation = transform.translation;
-
-                    if (Rect {
-                        left: translation.x - radius,
-                        right: translation.x + radius,
-                        top: translation.y + radius,
-                        bottom: translation.y - radius,
-                    }
-                    .contains(cursor_position))
-                    {
-                        commands.entity(entity).insert(Clicked);
-                    }
-                }
+    for (entity, mut sprite) in buttons.iter_mut() {
+        let color = match **hovered {
+            Some(hovered) if hovered == entity && mouse.pressed(MouseButton::Left) => {
+                PRESSED_BUTTON_COLOR
             }
+            Some(hovered) if hovered == entity => HOVERED_BUTTON_COLOR,
+            _ => Color::WHITE,
+        };
+
+        if sprite.color != color {
+            sprite.color = color;
         }
     }
 }
+
+/// Add `Clicked` component to the button that was just clicked
+fn press_button(
+    mut commands: Commands,
+    hovered: Res<HoveredButton>,
+    mouse: Res<Input<MouseButton>>,
+) {
+    if let (true, Some(button)) = (mouse.just_pressed(MouseButton::Left), **hovered) {
+        commands.entity(button).insert(Clicked);
+    }
+}
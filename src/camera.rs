@@ -1,14 +1,186 @@
-use bevy::prelude::*;
+use bevy::{
+    input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+    prelude::*,
+    render::camera::Camera2d,
+    window::WindowResized,
+};
+
+use crate::{
+    board::Board,
+    menu::in_game,
+    ui::{FitBoardButton, LogUi},
+};
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(init_camera);
+        app.add_startup_system(init_camera).add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(fit_board)
+                .with_system(zoom_camera.after(fit_board))
+                .with_system(pan_camera.after(fit_board)),
+        );
     }
 }
 
+/// The part of the window that the board is fit into, as fractions of the window from the bottom left.
+/// This is the space between the sidebars, and under the status bar, in `init_ui`.
+const BOARD_AREA: Rect<f32> = Rect {
+    left: 0.2,
+    right: 0.8,
+    top: 0.9,
+    bottom: 0.,
+};
+/// Space to leave around the board when fitting it, in world units
+const FIT_MARGIN: f32 = 20.;
+/// How much one line of scrolling zooms by
+const ZOOM_STEP: f32 = 1.1;
+/// Pixel scrolling (ex. from a touchpad) is counted in lines of this many pixels
+const SCROLL_LINE_PIXELS: f32 = 20.;
+// The camera's scale is in world units per pixel
+const MIN_SCALE: f32 = 0.2;
+const MAX_SCALE: f32 = 5.;
+/// How fast the arrow keys pan, in pixels per second
+const PAN_SPEED: f32 = 600.;
+
 fn init_camera(mut commands: Commands) {
     commands.spawn_bundle(OrthographicCameraBundle::new_2d());
     commands.spawn_bundle(UiCameraBundle::default());
 }
+
+/// Fit the whole board into the space between the UI when a game starts, when the window is resized,
+/// and when the player presses the Fit board button or F
+fn fit_board(
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+    buttons: Query<&Interaction, (With<FitBoardButton>, Changed<Interaction>)>,
+    mut resizes: EventReader<WindowResized>,
+    keys: Res<Input<KeyCode>>,
+    windows: Res<Windows>,
+    board: Res<Board>,
+) {
+    let resized = resizes.iter().count() > 0;
+    let pressed = keys.just_pressed(KeyCode::F)
+        || buttons
+            .iter()
+            .any(|interaction| matches!(interaction, Interaction::Clicked));
+
+    if !board.is_added() && !resized && !pressed {
+        return;
+    }
+
+    let window = windows.get_primary().unwrap();
+    let window_size = Vec2::new(window.width(), window.height());
+    let (min, max) = board
+        .layout
+        .building_positions
+        .iter()
+        .chain(board.layout.harbor_positions.iter())
+        .fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), position| (min.min(*position), max.max(*position)),
+        );
+    let (min, max) = (min - FIT_MARGIN, max + FIT_MARGIN);
+
+    let area_size = Vec2::new(
+        BOARD_AREA.right - BOARD_AREA.left,
+        BOARD_AREA.top - BOARD_AREA.bottom,
+    ) * window_size;
+    // The area's center, from the window's center, in pixels
+    let area_center = (Vec2::new(
+        BOARD_AREA.left + BOARD_AREA.right,
+        BOARD_AREA.bottom + BOARD_AREA.top,
+    ) / 2.
+        - 0.5)
+        * window_size;
+    let scale = ((max - min) / area_size)
+        .max_element()
+        .clamp(MIN_SCALE, MAX_SCALE);
+
+    for mut transform in cameras.iter_mut() {
+        let translation = (min + max) / 2. - area_center * scale;
+
+        transform.translation = translation.extend(transform.translation.z);
+        transform.scale = Vec3::new(scale, scale, 1.);
+    }
+}
+
+/// Zoom in and out with the mouse wheel, keeping the spot under the cursor in place.
+/// The wheel scrolls the game log instead, when it's hovered.
+fn zoom_camera(
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+    logs: Query<&Interaction, With<LogUi>>,
+    mut wheels: EventReader<MouseWheel>,
+    windows: Res<Windows>,
+) {
+    let lines: f32 = wheels
+        .iter()
+        .map(|wheel| match wheel.unit {
+            MouseScrollUnit::Line => wheel.y,
+            MouseScrollUnit::Pixel => wheel.y / SCROLL_LINE_PIXELS,
+        })
+        .sum();
+
+    if lines == 0.
+        || logs
+            .iter()
+            .any(|interaction| matches!(interaction, Interaction::Hovered))
+    {
+        return;
+    }
+
+    let window = windows.get_primary().unwrap();
+    // The cursor, from the window's center, in pixels
+    let cursor = window.cursor_position().map_or(Vec2::ZERO, |cursor| {
+        cursor - Vec2::new(window.width(), window.height()) / 2.
+    });
+
+    for mut transform in cameras.iter_mut() {
+        let old_scale = transform.scale.x;
+        let scale = (old_scale / ZOOM_STEP.powf(lines)).clamp(MIN_SCALE, MAX_SCALE);
+        let anchor = transform.translation.truncate() + cursor * old_scale;
+
+        transform.translation = (anchor - cursor * scale).extend(transform.translation.z);
+        transform.scale = Vec3::new(scale, scale, 1.);
+    }
+}
+
+/// Pan with the arrow keys, or by dragging with the right or middle mouse button
+fn pan_camera(
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+    mut motions: EventReader<MouseMotion>,
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    time: Res<Time>,
+) {
+    // In pixels, with y up
+    let mut pan = Vec2::ZERO;
+
+    // The motion is read every frame, so it doesn't pile up while the buttons are up
+    let drag: Vec2 = motions.iter().map(|motion| &motion.delta).sum();
+    if mouse.pressed(MouseButton::Right) || mouse.pressed(MouseButton::Middle) {
+        // The board follows the cursor, and the mouse's y goes down
+        pan += Vec2::new(-drag.x, drag.y);
+    }
+
+    for (key, direction) in [
+        (KeyCode::Left, -Vec2::X),
+        (KeyCode::Right, Vec2::X),
+        (KeyCode::Up, Vec2::Y),
+        (KeyCode::Down, -Vec2::Y),
+    ] {
+        if keys.pressed(key) {
+            pan += direction * PAN_SPEED * time.delta_seconds();
+        }
+    }
+
+    if pan == Vec2::ZERO {
+        return;
+    }
+
+    for mut transform in cameras.iter_mut() {
+        let scale = transform.scale.x;
+        transform.translation += (pan * scale).extend(0.);
+    }
+}
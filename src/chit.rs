@@ -1,9 +1,11 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
-use crate::{image::UpdateImages, random::Shuffle};
+use crate::{board::Board, image::UpdateImages, random::Shuffle};
 
 /// This is the little circular chip that's on the resource-producing tiles
-#[derive(Clone, Copy, Deref)]
+#[derive(Clone, Copy, Deref, Deserialize)]
+#[serde(transparent)]
 pub struct Chit(u8);
 
 /// Every valid `Chit` variant
@@ -38,7 +40,7 @@ impl Shuffle for Chit {
 pub struct ChitSlot(pub Option<Chit>);
 
 impl UpdateImages for ChitSlot {
-    fn image(self, _: usize) -> Option<&'static str> {
+    fn image(self, _: &Board, _: usize) -> Option<&'static str> {
         match *self {
             None => None,
             Some(Chit(2)) => Some("chit_2.png"),
@@ -7,6 +7,8 @@ pub enum PlayerColor {
     Orange,
     Red,
     White,
+    Green,
+    Brown,
 }
 
 impl From<PlayerColor> for String {
@@ -16,6 +18,8 @@ impl From<PlayerColor> for String {
             PlayerColor::Orange => "ORANGE",
             PlayerColor::Red => "RED",
             PlayerColor::White => "WHITE",
+            PlayerColor::Green => "GREEN",
+            PlayerColor::Brown => "BROWN",
         }
         .into()
     }
@@ -28,14 +32,20 @@ impl From<PlayerColor> for Color {
             PlayerColor::Orange => Color::rgb(1., 0.5, 0.),
             PlayerColor::Red => Color::RED,
             PlayerColor::White => default(),
+            PlayerColor::Green => Color::rgb(0., 0.67, 0.),
+            PlayerColor::Brown => Color::rgb(0.53, 0.27, 0.),
         }
     }
 }
 
+pub const COLOR_COUNT: usize = 6;
+
 /// Doesn't represent turn order
-pub const COLORS: [PlayerColor; 4] = [
+pub const COLORS: [PlayerColor; COLOR_COUNT] = [
     PlayerColor::Blue,
     PlayerColor::Orange,
     PlayerColor::Red,
     PlayerColor::White,
+    PlayerColor::Green,
+    PlayerColor::Brown,
 ];
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+use crate::{color::COLOR_COUNT, resource::Resource};
+
+/// Cards that cities make in Cities & Knights, instead of some of their resources.
+/// Each one pays for a track of city improvements.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Commodity {
+    Paper,
+    Cloth,
+    Coin,
+}
+
+impl Commodity {
+    /// The commodity that cities make on tiles that produce this resource, if any
+    pub fn from_resource(resource: Resource) -> Option<Self> {
+        match resource {
+            Resource::Lumber => Some(Self::Paper),
+            Resource::Wool => Some(Self::Cloth),
+            Resource::Ore => Some(Self::Coin),
+            Resource::Brick | Resource::Grain => None,
+        }
+    }
+
+    pub fn image(self) -> &'static str {
+        match self {
+            Self::Paper => "paper.png",
+            Self::Cloth => "cloth.png",
+            Self::Coin => "coin.png",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Paper => "Paper",
+            Self::Cloth => "Cloth",
+            Self::Coin => "Coin",
+        }
+    }
+
+    /// The city improvement track that this commodity pays for
+    pub fn track(self) -> &'static str {
+        match self {
+            Self::Paper => "Science",
+            Self::Cloth => "Trade",
+            Self::Coin => "Politics",
+        }
+    }
+}
+
+pub const COMMODITY_COUNT: usize = 3;
+
+pub const COMMODITIES: [Commodity; COMMODITY_COUNT] =
+    [Commodity::Paper, Commodity::Cloth, Commodity::Coin];
+
+/// Indexed by a `PlayerColor as usize`, and then a `Commodity as usize`
+#[derive(Default, Deref, DerefMut)]
+pub struct Commodities([[u8; COMMODITY_COUNT]; COLOR_COUNT]);
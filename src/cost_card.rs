@@ -0,0 +1,294 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::Board,
+    building::{building_count, can_build_city, can_build_settlement, BuildingSlot, BuildingType},
+    commodity::{Commodities, Commodity},
+    improvement::{can_improve_city, Improvements},
+    knight::{can_use_knight, KnightAction, KnightSlot},
+    menu::{in_game, GameSetup, Ruleset},
+    preset::BoardPresets,
+    resource::{Hands, RESOURCES},
+    road::{can_build_road, RoadSlot},
+    rules::{Cost, Rules},
+    ship::{can_build_ship, ShipSlot},
+    turn::{Players, Turn},
+    ui::CostCard,
+};
+
+pub struct CostCardPlugin;
+
+impl Plugin for CostCardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(fill_cost_card)
+                .with_system(update_buy_buttons),
+        );
+    }
+}
+
+/// Something that a button buys. The button is greyed out when the player can't buy it.
+#[derive(Clone, Component, Copy)]
+pub enum Purchase {
+    Road,
+    Settlement,
+    City,
+    Ship,
+    Knight(KnightAction),
+    /// Paid for with commodities, so it's not on the cost card
+    ImproveCity,
+}
+
+impl Purchase {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Road => "Road",
+            Self::Settlement => "Settlement",
+            Self::City => "City",
+            Self::Ship => "Ship",
+            Self::Knight(KnightAction::Build) => "Knight",
+            Self::Knight(KnightAction::Activate) => "Activate",
+            Self::Knight(KnightAction::Promote) => "Promote",
+            Self::ImproveCity => "Improve city",
+        }
+    }
+
+    /// The resources it costs, if it costs resources
+    fn cost(self, rules: &Rules) -> Option<Cost> {
+        match self {
+            Self::Road => Some(rules.road_cost),
+            Self::Settlement => Some(rules.settlement_cost),
+            Self::City => Some(rules.city_cost),
+            Self::Ship => Some(rules.ship_cost),
+            Self::Knight(action) => Some(action.cost(rules)),
+            Self::ImproveCity => None,
+        }
+    }
+}
+
+const COST_FONT_SIZE: f32 = 20.;
+const COST_ICON_SIZE: Val = Val::Px(20.);
+const DISABLED_BUTTON_COLOR: Color = Color::rgb(0.5, 0.5, 0.5);
+const DISABLED_TEXT_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+
+/// List what each piece costs, once the card is in the UI
+fn fill_cost_card(
+    mut commands: Commands,
+    cards: Query<Entity, Added<CostCard>>,
+    setup: Res<GameSetup>,
+    presets: Res<BoardPresets>,
+    rules: Res<Rules>,
+    assets: Res<AssetServer>,
+) {
+    let mut purchases = vec![Purchase::Road, Purchase::Settlement, Purchase::City];
+    if presets.get(&setup.board).has_open_sea() {
+        purchases.push(Purchase::Ship);
+    }
+    if setup.ruleset == Ruleset::CitiesAndKnights {
+        purchases.extend([
+            Purchase::Knight(KnightAction::Build),
+            Purchase::Knight(KnightAction::Activate),
+            Purchase::Knight(KnightAction::Promote),
+        ]);
+    }
+
+    for card in cards.iter() {
+        commands.entity(card).with_children(|parent| {
+            for purchase in &purchases {
+                let cost = match purchase.cost(&rules) {
+                    Some(cost) => cost,
+                    None => continue,
+                };
+
+                // Cost row
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        color: Color::NONE.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                format!("{} ", purchase.name()),
+                                TextStyle {
+                                    font: assets.load("FiraSans-Bold.ttf"),
+                                    font_size: COST_FONT_SIZE,
+                                    color: default(),
+                                },
+                                default(),
+                            ),
+                            ..default()
+                        });
+
+                        // One icon per card
+                        for resource in RESOURCES {
+                            for _ in 0..cost[resource as usize] {
+                                parent.spawn_bundle(ImageBundle {
+                                    style: Style {
+                                        size: Size::new(COST_ICON_SIZE, COST_ICON_SIZE),
+                                        ..default()
+                                    },
+                                    image: assets.load(resource.image()).into(),
+                                    ..default()
+                                });
+                            }
+                        }
+                    });
+            }
+        });
+    }
+}
+
+/// Whether the predicate holds for any of the board indices below the count.
+/// Ranges are `Reflect`, so `(0..count).any(..)` finds `Reflect::any` instead of `Iterator::any`.
+fn anywhere(count: usize, predicate: impl FnMut(usize) -> bool) -> bool {
+    Iterator::any(&mut (0..count), predicate)
+}
+
+/// Grey out the buttons for things that the player can't buy right now,
+/// because it's not their build phase, they can't pay, they're out of pieces, or there's nowhere to put it.
+/// Pressing them already does nothing then.
+fn update_buy_buttons(
+    mut buttons: Query<(&Purchase, &mut UiColor, &Children), With<Button>>,
+    mut texts: Query<&mut Text>,
+    buildings: Query<&BuildingSlot>,
+    roads: Query<&RoadSlot>,
+    ships: Query<&ShipSlot>,
+    knights: Query<&KnightSlot>,
+    board: Res<Board>,
+    players: Res<Players>,
+    hands: Res<Hands>,
+    commodities: Res<Commodities>,
+    improvements: Res<Improvements>,
+    rules: Res<Rules>,
+    turn: Res<Turn>,
+) {
+    if !turn.is_changed()
+        && !hands.is_changed()
+        && !commodities.is_changed()
+        && !improvements.is_changed()
+    {
+        return;
+    }
+
+    let color = match *turn {
+        Turn::Build { player, .. } => Some(players[player]),
+        _ => None,
+    };
+    // Every edge's ship and every corner's knight, by board index
+    let ship_slots: Vec<_> = board
+        .ships
+        .iter()
+        .map(|ship| **ships.get(*ship).unwrap())
+        .collect();
+    let knight_slots: Vec<_> = board
+        .knights
+        .iter()
+        .map(|knight| **knights.get(*knight).unwrap())
+        .collect();
+
+    for (purchase, mut button_color, children) in buttons.iter_mut() {
+        let can_buy = match color {
+            Some(color) => {
+                let hand = hands[color as usize];
+                let can_afford = match purchase.cost(&rules) {
+                    Some(cost) => cost.can_afford(hand),
+                    None => true,
+                };
+
+                can_afford
+                    && match *purchase {
+                        Purchase::Road => {
+                            let count = roads
+                                .iter()
+                                .filter(|road| matches!(***road, Some(road) if road.color == color))
+                                .count();
+
+                            count < rules.max_roads
+                                && anywhere(board.roads.len(), |index| {
+                                    can_build_road(
+                                        index, color, false, &board, &buildings, &roads, &ships,
+                                    )
+                                })
+                        }
+                        Purchase::Settlement => {
+                            building_count(color, BuildingType::Settlement, &buildings)
+                                < rules.max_settlements
+                                && anywhere(board.buildings.len(), |index| {
+                                    can_build_settlement(
+                                        index, color, false, &board, &buildings, &roads, &ships,
+                                        &knights,
+                                    )
+                                })
+                        }
+                        Purchase::City => {
+                            building_count(color, BuildingType::City, &buildings) < rules.max_cities
+                                && anywhere(board.buildings.len(), |index| {
+                                    can_build_city(index, color, &board, &buildings)
+                                })
+                        }
+                        Purchase::Ship => {
+                            let count = ship_slots
+                                .iter()
+                                .filter(|ship| matches!(ship, Some(ship) if ship.color == color))
+                                .count();
+
+                            count < rules.max_ships
+                                && anywhere(board.ships.len(), |index| {
+                                    can_build_ship(
+                                        index,
+                                        None,
+                                        color,
+                                        &board,
+                                        &buildings,
+                                        &roads,
+                                        &ship_slots,
+                                    )
+                                })
+                        }
+                        Purchase::Knight(action) => anywhere(board.knights.len(), |index| {
+                            can_use_knight(
+                                action,
+                                index,
+                                color,
+                                improvements[color as usize][Commodity::Coin as usize],
+                                &board,
+                                &buildings,
+                                &roads,
+                                &ships,
+                                &knight_slots,
+                                &rules,
+                            )
+                        }),
+                        Purchase::ImproveCity => {
+                            can_improve_city(color, &buildings, &commodities, &improvements)
+                        }
+                    }
+            }
+            None => false,
+        };
+
+        *button_color = if can_buy {
+            Color::WHITE
+        } else {
+            DISABLED_BUTTON_COLOR
+        }
+        .into();
+
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].style.color = if can_buy {
+                    Color::BLACK
+                } else {
+                    DISABLED_TEXT_COLOR
+                };
+            }
+        }
+    }
+}
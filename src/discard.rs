@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+use crate::{
+    button::{pressed, Clicked, MaybePressed},
+    color::COLOR_COUNT,
+    log::{GameLog, LogEvent},
+    menu::in_game,
+    resource::{Hands, Resource, RESOURCES},
+    turn::{Players, Turn},
+    ui::ChoiceBar,
+};
+
+pub struct DiscardPlugin;
+
+impl Plugin for DiscardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Discards>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(update_discard_buttons)
+                .with_system(press_discard_button),
+        );
+    }
+}
+
+/// How many cards each player has left to discard after a 7 is rolled.
+/// Indexed by a `PlayerColor as usize`.
+#[derive(Default, Deref, DerefMut)]
+pub struct Discards([u8; COLOR_COUNT]);
+
+impl Discards {
+    /// Players with more resources than `limit` discard half of them, rounded down.
+    /// Commodities don't count.
+    pub fn new(hands: &Hands, limit: u8) -> Self {
+        Self(hands.map(|hand| {
+            let count = hand.iter().sum::<u8>();
+
+            if count > limit {
+                count / 2
+            } else {
+                0
+            }
+        }))
+    }
+
+    /// Gets the next player, in turn order from `player`, that has cards to discard.
+    /// If `after` is given, only players after them are considered.
+    pub fn next_discarder(
+        &self,
+        players: &Players,
+        player: usize,
+        after: Option<usize>,
+    ) -> Option<usize> {
+        let start = after.map_or(0, |after| {
+            (after + players.len() - player) % players.len() + 1
+        });
+
+        (start..players.len())
+            .map(|offset| (player + offset) % players.len())
+            .find(|&discarder| self[players[discarder] as usize] > 0)
+    }
+}
+
+/// A button to discard a resource
+#[derive(Component, Deref)]
+pub struct DiscardButton(Resource);
+
+const DISCARD_SIZE: Val = Val::Px(48.);
+
+/// Show a button for each resource the discarder has, while someone is discarding, and hide them otherwise
+fn update_discard_buttons(
+    mut commands: Commands,
+    bars: Query<Entity, With<ChoiceBar>>,
+    buttons: Query<Entity, With<DiscardButton>>,
+    players: Res<Players>,
+    hands: Res<Hands>,
+    turn: Res<Turn>,
+    assets: Res<AssetServer>,
+) {
+    // A resource's button goes away once the discarder has none left
+    if !turn.is_changed() && !hands.is_changed() {
+        return;
+    }
+
+    // Other choices share the bar, so only these buttons are cleared
+    for button in buttons.iter() {
+        commands.entity(button).despawn_recursive();
+    }
+
+    if let Turn::Discard { discarder, .. } = *turn {
+        let hand = hands[players[discarder] as usize];
+
+        for bar in bars.iter() {
+            commands.entity(bar).with_children(|parent| {
+                for resource in RESOURCES
+                    .into_iter()
+                    .filter(|resource| hand[*resource as usize] > 0)
+                {
+                    parent
+                        .spawn_bundle(ButtonBundle {
+                            style: Style {
+                                size: Size::new(DISCARD_SIZE, DISCARD_SIZE),
+                                ..default()
+                            },
+                            image: assets.load(resource.image()).into(),
+                            ..default()
+                        })
+                        .insert(DiscardButton(resource));
+                }
+            });
+        }
+    }
+}
+
+/// Take the resource the discarder picked, and move on once they've discarded enough
+fn press_discard_button(
+    mut commands: Commands,
+    buttons: Query<(Entity, &Interaction, &DiscardButton, Option<&Clicked>), MaybePressed>,
+    players: Res<Players>,
+    mut hands: ResMut<Hands>,
+    mut discards: ResMut<Discards>,
+    mut log: ResMut<GameLog>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::Discard { player, discarder } = *turn {
+        for (button, interaction, resource, clicked) in buttons.iter() {
+            let color = players[discarder] as usize;
+
+            if pressed(&mut commands, button, interaction, clicked)
+                && discards[color] > 0
+                && hands[color][**resource as usize] > 0
+            {
+                hands[color][**resource as usize] -= 1;
+                discards[color] -= 1;
+                log.push(
+                    players[discarder],
+                    LogEvent::Discarded {
+                        resource: **resource,
+                    },
+                );
+
+                if discards[color] == 0 {
+                    *turn = match discards.next_discarder(&players, player, Some(discarder)) {
+                        Some(discarder) => Turn::Discard { player, discarder },
+                        None => turn.next(players.len()),
+                    };
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use crate::{
+    button::{pressed, Clicked, MaybePressed},
+    menu::in_game,
+    turn::{Players, Turn},
+    ui::{ChoiceBar, BUTTON_FONT_SIZE},
+};
+
+pub struct DraftPlugin;
+
+impl Plugin for DraftPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Draft>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(update_draft_buttons)
+                .with_system(press_draft_button),
+        );
+    }
+}
+
+/// The places in the turn order that have been picked so far, in the draft setup mode.
+/// Players pick in the order they were seated, which is random.
+#[derive(Default)]
+pub struct Draft {
+    /// By place in the turn order, the index of the player who picked it
+    picks: Vec<Option<usize>>,
+    /// The turn to start once everyone has picked
+    setup: Turn,
+}
+
+impl Draft {
+    pub fn new(player_count: usize, setup: Turn) -> Self {
+        Self {
+            picks: vec![None; player_count],
+            setup,
+        }
+    }
+}
+
+/// A button to pick a place in the turn order
+#[derive(Component, Deref)]
+pub struct DraftButton(usize);
+
+/// "1st", "2nd", and so on
+fn ordinal(place: usize) -> String {
+    match place {
+        1 => "1st".to_string(),
+        2 => "2nd".to_string(),
+        3 => "3rd".to_string(),
+        _ => format!("{place}th"),
+    }
+}
+
+/// Show a button for each place that hasn't been picked while the draft is going, and hide them otherwise
+fn update_draft_buttons(
+    mut commands: Commands,
+    bars: Query<Entity, With<ChoiceBar>>,
+    buttons: Query<Entity, With<DraftButton>>,
+    draft: Res<Draft>,
+    turn: Res<Turn>,
+    assets: Res<AssetServer>,
+) {
+    if !turn.is_changed() {
+        return;
+    }
+
+    // Other choices share the bar, so only these buttons are cleared
+    for button in buttons.iter() {
+        commands.entity(button).despawn_recursive();
+    }
+
+    if let Turn::Draft { .. } = *turn {
+        for bar in bars.iter() {
+            commands.entity(bar).with_children(|parent| {
+                for (place, _) in draft
+                    .picks
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, pick)| pick.is_none())
+                {
+                    parent
+                        .spawn_bundle(ButtonBundle::default())
+                        .insert(DraftButton(place))
+                        .with_children(|parent| {
+                            // Button text
+                            parent.spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    ordinal(place + 1),
+                                    TextStyle {
+                                        font: assets.load("FiraSans-Bold.ttf"),
+                                        font_size: BUTTON_FONT_SIZE,
+                                        color: Color::BLACK,
+                                    },
+                                    default(),
+                                ),
+                                focus_policy: FocusPolicy::Pass,
+                                ..default()
+                            });
+                        });
+                }
+            });
+        }
+    }
+}
+
+/// Give the player the place they picked, and reseat everyone once the last player has picked
+fn press_draft_button(
+    mut commands: Commands,
+    buttons: Query<(Entity, &Interaction, &DraftButton, Option<&Clicked>), MaybePressed>,
+    mut players: ResMut<Players>,
+    mut draft: ResMut<Draft>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::Draft { player } = *turn {
+        for (button, interaction, place, clicked) in buttons.iter() {
+            if pressed(&mut commands, button, interaction, clicked)
+                && draft.picks[**place].is_none()
+            {
+                draft.picks[**place] = Some(player);
+
+                *turn = if player == players.len() - 1 {
+                    let order: Vec<_> = draft.picks.iter().flatten().copied().collect();
+                    players.reseat(&order);
+                    draft.setup
+                } else {
+                    turn.next(players.len())
+                };
+            }
+        }
+    }
+}
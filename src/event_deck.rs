@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use rand::{prelude::SliceRandom, thread_rng};
+
+use crate::{menu::in_game, ui::DeckText};
+
+pub struct EventDeckPlugin;
+
+impl Plugin for EventDeckPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EventDeck>()
+            .add_system(update_deck_text.with_run_criteria(in_game));
+    }
+}
+
+/// Something that happens along with a card's roll
+#[derive(Clone, Copy)]
+pub enum Event {
+    /// Everyone picks a resource
+    PlentifulYear,
+    /// Nobody gets anything this turn
+    Earthquake,
+}
+
+impl Event {
+    fn description(self) -> &'static str {
+        match self {
+            Self::PlentifulYear => "Plentiful year: everyone picks a resource",
+            Self::Earthquake => "Earthquake: nothing is produced",
+        }
+    }
+}
+
+/// A card in the event deck. It stands in for a roll of the dice.
+#[derive(Clone, Copy)]
+pub struct EventCard {
+    pub dice: [u8; 2],
+    pub event: Option<Event>,
+}
+
+/// Which cards carry events, by their dice
+const EVENTS: [([u8; 2], Event); 3] = [
+    ([1, 4], Event::PlentifulYear),
+    ([6, 3], Event::PlentifulYear),
+    ([2, 4], Event::Earthquake),
+];
+
+/// The deck is reshuffled once this few cards are left, so the last few rolls can't be counted
+const RESHUFFLE_AT: usize = 5;
+
+/// An alternative to dice, with one card for every way that two dice can land,
+/// so every number comes up about as often as it should, even in a short game
+pub struct EventDeck {
+    cards: Vec<EventCard>,
+    /// The event on the last card drawn, if any
+    pub last_event: Option<Event>,
+}
+
+impl Default for EventDeck {
+    fn default() -> Self {
+        let mut cards: Vec<_> = (1..=6)
+            .flat_map(|die_1| (1..=6).map(move |die_2| [die_1, die_2]))
+            .map(|dice| EventCard {
+                dice,
+                event: EVENTS
+                    .iter()
+                    .find(|(other, _)| *other == dice)
+                    .map(|(_, event)| *event),
+            })
+            .collect();
+        cards.shuffle(&mut thread_rng());
+
+        Self {
+            cards,
+            last_event: None,
+        }
+    }
+}
+
+impl EventDeck {
+    /// Draw a card, reshuffling first if the deck is running low
+    pub fn draw(&mut self) -> EventCard {
+        if self.cards.len() <= RESHUFFLE_AT {
+            *self = default();
+        }
+
+        let card = self.cards.pop().unwrap();
+        self.last_event = card.event;
+        card
+    }
+}
+
+/// Show how many cards are left before the reshuffle, and the last event
+fn update_deck_text(mut texts: Query<&mut Text, With<DeckText>>, deck: Res<EventDeck>) {
+    if deck.is_changed() {
+        for mut text in texts.iter_mut() {
+            let left = deck.cards.len() - RESHUFFLE_AT;
+
+            text.sections[0].value = match deck.last_event {
+                Some(event) => format!("{}. {left} cards to reshuffle", event.description()),
+                None => format!("{left} cards to reshuffle"),
+            };
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use bevy::{prelude::*, window::CursorMoved};
+
+use crate::{
+    board::{Board, BoardIndex},
+    building::{Building, BuildingSlot, BuildingType},
+    button::{BuildingButton, HoveredButton},
+    chit::ChitSlot,
+    harbor::{Harbor, HarborSlot},
+    image::UpdateImages,
+    menu::{in_game, GameSetup, Ruleset},
+    overlay::{Odds, Overlay},
+    road::{Road, RoadSlot},
+    robber::RobberSlot,
+    rules::Rules,
+    ship::{Ship, ShipSlot},
+    tile::{Tile, TileEffect},
+    turn::{Players, Turn},
+    ui::Tooltip,
+};
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(update_ghost)
+                .with_system(update_tooltip),
+        );
+    }
+}
+
+/// A see-through preview of the piece that the player is about to place, over the hovered button
+#[derive(Component)]
+pub struct Ghost;
+
+pub const GHOST_COLOR: Color = Color::rgba(1., 1., 1., 0.6);
+/// How far the tooltip is from the cursor, in pixels
+const TOOLTIP_OFFSET: f32 = 16.;
+
+/// Move the ghost to the hovered button, and show the piece that would go there
+fn update_ghost(
+    mut ghosts: Query<(&mut Handle<Image>, &mut Transform, &mut Visibility), With<Ghost>>,
+    buttons: Query<(&BoardIndex, &Transform), Without<Ghost>>,
+    board: Res<Board>,
+    players: Res<Players>,
+    setup: Res<GameSetup>,
+    hovered: Res<HoveredButton>,
+    turn: Res<Turn>,
+    assets: Res<AssetServer>,
+) {
+    if !hovered.is_changed() && !turn.is_changed() {
+        return;
+    }
+
+    let image = hovered.and_then(|button| {
+        let (index, _) = buttons.get(button).ok()?;
+        let color = players[turn.player()?];
+        let building = |building_type| {
+            BuildingSlot(Some(Building {
+                building_type,
+                color,
+            }))
+            .image(&board, **index)
+        };
+
+        match *turn {
+            Turn::Setup {
+                round, road: false, ..
+            } => building(
+                if round == 2 && setup.ruleset == Ruleset::CitiesAndKnights {
+                    BuildingType::City
+                } else {
+                    BuildingType::Settlement
+                },
+            ),
+            Turn::BuildSettlement { .. } => building(BuildingType::Settlement),
+            Turn::BuildCity { .. } => building(BuildingType::City),
+            Turn::Setup { road: true, .. } | Turn::BuildRoad { .. } => {
+                RoadSlot(Some(Road { color })).image(&board, **index)
+            }
+            Turn::BuildShip { .. } | Turn::MoveShip { from: Some(_), .. } => {
+                ShipSlot(Some(Ship { color })).image(&board, **index)
+            }
+            _ => None,
+        }
+    });
+
+    for (mut texture, mut transform, mut visibility) in ghosts.iter_mut() {
+        match (image, hovered.and_then(|button| buttons.get(button).ok())) {
+            (Some(image), Some((_, button_transform))) => {
+                *texture = assets.load(image);
+                transform.translation.x = button_transform.translation.x;
+                transform.translation.y = button_transform.translation.y;
+                visibility.is_visible = true;
+            }
+            _ => visibility.is_visible = false,
+        }
+    }
+}
+
+/// What a tile gives, for the tooltip
+fn tile_yield(tile: Tile) -> Option<&'static str> {
+    match tile.effect()? {
+        TileEffect::Produce(resource) => Some(resource.name()),
+        TileEffect::ChooseResource => Some("Gold"),
+        TileEffect::Erupt => Some("Volcano"),
+    }
+}
+
+/// Next to the cursor, show what the hovered corner would yield, and the harbor it would trade at, if any,
+/// while the player is placing a settlement or city. With the overlay on, it also shows the cards per roll of each resource.
+/// The text is only rewritten when something changes, since that lays out the UI again.
+fn update_tooltip(
+    mut tooltips: Query<(&mut Text, &mut Style, &mut Visibility), With<Tooltip>>,
+    buttons: Query<&BoardIndex, With<BuildingButton>>,
+    tiles: Query<&Tile>,
+    chits: Query<&ChitSlot>,
+    harbors: Query<&HarborSlot>,
+    robbers: Query<&RobberSlot>,
+    board: Res<Board>,
+    rules: Res<Rules>,
+    overlay: Res<Overlay>,
+    hovered: Res<HoveredButton>,
+    turn: Res<Turn>,
+    windows: Res<Windows>,
+    mut cursor_moved: EventReader<CursorMoved>,
+) {
+    let moved = cursor_moved.iter().count() > 0;
+    if !moved && !hovered.is_changed() && !overlay.is_changed() && !turn.is_changed() {
+        return;
+    }
+
+    // Building buttons also pick who to steal from, and where knights go
+    let placing = matches!(
+        *turn,
+        Turn::Setup { road: false, .. } | Turn::BuildSettlement { .. } | Turn::BuildCity { .. }
+    );
+    let index = hovered
+        .and_then(|button| buttons.get(button).ok())
+        .filter(|_| placing);
+    let cursor = windows.get_primary().unwrap().cursor_position();
+
+    for (mut text, mut style, mut visibility) in tooltips.iter_mut() {
+        let (index, cursor) = match (index, cursor) {
+            (Some(index), Some(cursor)) => (index, cursor),
+            _ => {
+                if visibility.is_visible {
+                    visibility.is_visible = false;
+                }
+                continue;
+            }
+        };
+
+        let mut lines: Vec<_> = board.layout.building_tiles[**index]
+            .iter()
+            .filter_map(|tile| {
+                let name = tile_yield(*tiles.get(board.tiles[*tile]).unwrap())?;
+                let chit = (**chits.get(board.chits[*tile]).unwrap())?;
+
+                Some(format!("{} {name}", *chit))
+            })
+            .collect();
+
+        if **overlay {
+            let odds = Odds::new(**index, &board, &tiles, &chits, &robbers);
+
+            lines.extend(
+                odds.by_name()
+                    .map(|(name, cards)| format!("{cards:.2} {name} per roll")),
+            );
+        }
+
+        if let Some(harbor) = board.layout.building_harbors[**index]
+            .and_then(|harbor| **harbors.get(board.harbors[harbor]).unwrap())
+        {
+            lines.push(match harbor {
+                Harbor::Resource(resource) => format!(
+                    "{}:1 {} harbor",
+                    rules.resource_harbor_ratio,
+                    resource.name()
+                ),
+                Harbor::Any => format!("{}:1 harbor", rules.harbor_ratio),
+            });
+        }
+
+        if lines.is_empty() {
+            lines.push("Nothing".to_string());
+        }
+
+        text.sections[0].value = lines.join("\n");
+        style.position.left = Val::Px(cursor.x + TOOLTIP_OFFSET);
+        style.position.bottom = Val::Px(cursor.y + TOOLTIP_OFFSET);
+        visibility.is_visible = true;
+    }
+}
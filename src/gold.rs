@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+
+use crate::{
+    button::{pressed, Clicked, MaybePressed},
+    color::COLOR_COUNT,
+    log::{GameLog, LogEvent},
+    menu::in_game,
+    resource::{Hands, Resource, RESOURCES},
+    turn::{Players, Turn},
+    ui::ChoiceBar,
+};
+
+pub struct GoldPlugin;
+
+impl Plugin for GoldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GoldChoices>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(update_choice_bar)
+                .with_system(press_choice_button),
+        );
+    }
+}
+
+/// How many resources each player has left to choose from gold fields.
+/// Indexed by a `PlayerColor as usize`.
+#[derive(Default, Deref, DerefMut)]
+pub struct GoldChoices([u8; COLOR_COUNT]);
+
+impl GoldChoices {
+    /// Gets the next player, in turn order from `player`, that has resources to choose.
+    /// If `after` is given, only players after them are considered.
+    pub fn next_chooser(
+        &self,
+        players: &Players,
+        player: usize,
+        after: Option<usize>,
+    ) -> Option<usize> {
+        let start = after.map_or(0, |after| {
+            (after + players.len() - player) % players.len() + 1
+        });
+
+        (start..players.len())
+            .map(|offset| (player + offset) % players.len())
+            .find(|&chooser| self[players[chooser] as usize] > 0)
+    }
+}
+
+/// A button to choose a resource from a gold field
+#[derive(Component, Deref)]
+pub struct ChoiceButton(Resource);
+
+const CHOICE_SIZE: Val = Val::Px(48.);
+
+/// Show a button for each resource while someone is choosing, and hide them otherwise
+fn update_choice_bar(
+    mut commands: Commands,
+    bars: Query<Entity, With<ChoiceBar>>,
+    buttons: Query<Entity, With<ChoiceButton>>,
+    turn: Res<Turn>,
+    assets: Res<AssetServer>,
+) {
+    if !turn.is_changed() {
+        return;
+    }
+
+    // Other choices share the bar, so only these buttons are cleared
+    for button in buttons.iter() {
+        commands.entity(button).despawn_recursive();
+    }
+
+    if let Turn::ChooseResource { .. } = *turn {
+        for bar in bars.iter() {
+            commands.entity(bar).with_children(|parent| {
+                for resource in RESOURCES {
+                    parent
+                        .spawn_bundle(ButtonBundle {
+                            style: Style {
+                                size: Size::new(CHOICE_SIZE, CHOICE_SIZE),
+                                ..default()
+                            },
+                            image: assets.load(resource.image()).into(),
+                            ..default()
+                        })
+                        .insert(ChoiceButton(resource));
+                }
+            });
+        }
+    }
+}
+
+/// Give the chooser the resource they picked, and move on once they've picked them all
+fn press_choice_button(
+    mut commands: Commands,
+    buttons: Query<(Entity, &Interaction, &ChoiceButton, Option<&Clicked>), MaybePressed>,
+    players: Res<Players>,
+    mut hands: ResMut<Hands>,
+    mut gold_choices: ResMut<GoldChoices>,
+    mut log: ResMut<GameLog>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::ChooseResource { player, chooser } = *turn {
+        for (button, interaction, resource, clicked) in buttons.iter() {
+            let color = players[chooser] as usize;
+
+            if pressed(&mut commands, button, interaction, clicked) && gold_choices[color] > 0 {
+                hands[color][**resource as usize] += 1;
+                gold_choices[color] -= 1;
+                log.push(
+                    players[chooser],
+                    LogEvent::Chose {
+                        resource: **resource,
+                    },
+                );
+
+                if gold_choices[color] == 0 {
+                    *turn = match gold_choices.next_chooser(&players, player, Some(chooser)) {
+                        Some(chooser) => Turn::ChooseResource { player, chooser },
+                        None => turn.next(players.len()),
+                    };
+                }
+            }
+        }
+    }
+}
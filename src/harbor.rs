@@ -1,8 +1,9 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
-use crate::{image::UpdateImages, random::Shuffle, resource::Resource};
+use crate::{board::Board, image::UpdateImages, random::Shuffle, resource::Resource};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize)]
 pub enum Harbor {
     Resource(Resource),
     Any,
@@ -37,7 +38,7 @@ impl Shuffle for Option<Harbor> {
 pub struct HarborSlot(pub Option<Harbor>);
 
 impl UpdateImages for HarborSlot {
-    fn image(self, _: usize) -> Option<&'static str> {
+    fn image(self, _: &Board, _: usize) -> Option<&'static str> {
         match *self {
             None => None,
             Some(Harbor::Resource(Resource::Brick)) => Some("brick_harbor.png"),
@@ -0,0 +1,361 @@
+use std::{collections::HashMap, f32::consts::TAU};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{preset::Terrain, road::RoadOrientation};
+
+/// Axial coordinates of a hex. The hexes are pointy-topped,
+/// `q` increases to the right, and `r` increases down and to the right.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+/// Half the distance between the centers of horizontally adjacent hexes
+const HALF_TILE_WIDTH: f32 = 55.;
+/// Vertical distance between the centers of hexes in adjacent rows
+const ROW_HEIGHT: i32 = 95;
+/// Vertical distance from a hex's center to its top corner
+const CORNER_HEIGHT: i32 = 63;
+/// Vertical distance from a hex's center to its upper side corners
+const SIDE_CORNER_HEIGHT: i32 = ROW_HEIGHT - CORNER_HEIGHT;
+/// How far harbors are from the center of their tile, relative to the edge they're on
+const HARBOR_DISTANCE: f32 = 1.5;
+
+impl Hex {
+    /// Whether the hexes share an edge
+    pub fn is_neighbor(self, other: Self) -> bool {
+        matches!(
+            (other.q - self.q, other.r - self.r),
+            (1, 0) | (-1, 0) | (0, 1) | (0, -1) | (1, -1) | (-1, 1)
+        )
+    }
+
+    /// Which column of half-hexes the center is in
+    fn column(self) -> i32 {
+        2 * self.q + self.r
+    }
+
+    fn y(self) -> i32 {
+        -ROW_HEIGHT * self.r
+    }
+
+    pub fn position(self) -> Vec2 {
+        Vec2::new(HALF_TILE_WIDTH * self.column() as f32, self.y() as f32)
+    }
+
+    /// This hex's corners, clockwise from the top
+    fn corners(self) -> [Corner; 6] {
+        let (column, y) = (self.column(), self.y());
+
+        [
+            Corner::new(column, y + CORNER_HEIGHT),
+            Corner::new(column + 1, y + SIDE_CORNER_HEIGHT),
+            Corner::new(column + 1, y - SIDE_CORNER_HEIGHT),
+            Corner::new(column, y - CORNER_HEIGHT),
+            Corner::new(column - 1, y - SIDE_CORNER_HEIGHT),
+            Corner::new(column - 1, y + SIDE_CORNER_HEIGHT),
+        ]
+    }
+}
+
+/// A hex corner, where buildings go. Exact, so corners shared by hexes compare equal.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct Corner {
+    /// In half-hex widths
+    column: i32,
+    y: i32,
+}
+
+impl Corner {
+    fn new(column: i32, y: i32) -> Self {
+        Self { column, y }
+    }
+
+    fn position(self) -> Vec2 {
+        Vec2::new(HALF_TILE_WIDTH * self.column as f32, self.y as f32)
+    }
+
+    /// Sorts corners into zigzagging rows from top to bottom, then left to right
+    fn order(self) -> (i32, i32) {
+        ((CORNER_HEIGHT - self.y).div_euclid(ROW_HEIGHT), self.column)
+    }
+}
+
+/// Where everything on a board goes, and what's next to what, by board index.
+/// Derived from the board's hexes, so it works for any shape of board.
+pub struct Layout {
+    pub tile_positions: Vec<Vec2>,
+    /// Harbors go just off the coast, on the edges that have one tile, or the edges that the board picked
+    pub harbor_positions: Vec<Vec2>,
+    pub road_positions: Vec<Vec2>,
+    pub road_orientations: Vec<RoadOrientation>,
+    pub road_buildings: Vec<[usize; 2]>,
+    pub building_positions: Vec<Vec2>,
+    pub building_tiles: Vec<Vec<usize>>,
+    /// The harbor each corner can trade at, if any, by harbor index
+    pub building_harbors: Vec<Option<usize>>,
+    pub building_roads: Vec<Vec<usize>>,
+    pub building_buildings: Vec<Vec<usize>>,
+    /// Whether an edge touches land, so a road can go there
+    pub road_land: Vec<bool>,
+    /// Whether an edge touches sea, so a ship can go there
+    pub road_sea: Vec<bool>,
+    /// Which island a corner is on, if it touches land, so a building can go there.
+    /// Islands are numbered in tile order.
+    pub building_islands: Vec<Option<usize>>,
+}
+
+impl Layout {
+    /// Lay out a board with the given tiles. Tile board indices are in the order given.
+    /// `harbors` are pairs of a land hex and the sea hex that its harbor faces, if the board picks them.
+    pub fn new(hexes: &[(Hex, Terrain)], harbors: Option<&[[Hex; 2]]>) -> Self {
+        let tile_sea: Vec<_> = hexes
+            .iter()
+            .map(|(_, terrain)| *terrain == Terrain::Sea)
+            .collect();
+        let hexes: Vec<_> = hexes.iter().map(|(hex, _)| *hex).collect();
+        let tile_positions: Vec<_> = hexes.iter().map(|hex| hex.position()).collect();
+
+        // Islands, by flood fill
+        let mut tile_islands = vec![None; hexes.len()];
+        let mut island_count = 0;
+        for start in 0..hexes.len() {
+            if tile_sea[start] || tile_islands[start].is_some() {
+                continue;
+            }
+
+            let mut stack = vec![start];
+            tile_islands[start] = Some(island_count);
+            while let Some(tile) = stack.pop() {
+                for other in 0..hexes.len() {
+                    if !tile_sea[other]
+                        && tile_islands[other].is_none()
+                        && hexes[tile].is_neighbor(hexes[other])
+                    {
+                        tile_islands[other] = Some(island_count);
+                        stack.push(other);
+                    }
+                }
+            }
+
+            island_count += 1;
+        }
+
+        // Buildings. Corners out in the sea are only kept if they're surrounded by sea tiles,
+        // so ships can pass through them, but not sail off the edge of the board.
+        let mut corner_tiles = HashMap::<Corner, Vec<usize>>::new();
+        for (tile, hex) in hexes.iter().enumerate() {
+            for corner in hex.corners() {
+                corner_tiles.entry(corner).or_default().push(tile);
+            }
+        }
+
+        let mut corners: Vec<_> = corner_tiles
+            .iter()
+            .filter(|(_, tiles)| tiles.len() == 3 || tiles.iter().any(|tile| !tile_sea[*tile]))
+            .map(|(corner, _)| *corner)
+            .collect();
+        corners.sort_by_key(|corner| corner.order());
+
+        let building_indices: HashMap<_, _> = corners
+            .iter()
+            .enumerate()
+            .map(|(i, corner)| (*corner, i))
+            .collect();
+
+        let building_tiles: Vec<_> = corners
+            .iter()
+            .map(|corner| {
+                let mut tiles = corner_tiles[corner].clone();
+                tiles.sort_unstable();
+                tiles
+            })
+            .collect();
+        let building_islands = building_tiles
+            .iter()
+            .map(|tiles| tiles.iter().find_map(|tile| tile_islands[*tile]))
+            .collect();
+
+        // Roads, along with the tiles they border
+        let mut edges = HashMap::<[usize; 2], Vec<usize>>::new();
+        for (tile, hex) in hexes.iter().enumerate() {
+            let hex_corners = hex.corners();
+
+            for i in 0..hex_corners.len() {
+                if let (Some(start), Some(end)) = (
+                    building_indices.get(&hex_corners[i]),
+                    building_indices.get(&hex_corners[(i + 1) % hex_corners.len()]),
+                ) {
+                    let mut ends = [*start, *end];
+                    ends.sort_unstable();
+                    edges.entry(ends).or_default().push(tile);
+                }
+            }
+        }
+
+        // An edge along the outside of the sea goes nowhere
+        edges.retain(|_, tiles| tiles.len() == 2 || tiles.iter().any(|tile| !tile_sea[*tile]));
+
+        let mut edges: Vec<_> = edges.into_iter().collect();
+        // Top to bottom, then left to right, by midpoint
+        edges.sort_by_key(|([start, end], _)| {
+            let (start, end) = (corners[*start], corners[*end]);
+            (-(start.y + end.y), start.column + end.column)
+        });
+
+        let road_buildings: Vec<_> = edges.iter().map(|(ends, _)| *ends).collect();
+        let road_land = edges
+            .iter()
+            .map(|(_, tiles)| tiles.iter().any(|tile| !tile_sea[*tile]))
+            .collect();
+        let road_sea = edges
+            .iter()
+            .map(|(_, tiles)| tiles.iter().any(|tile| tile_sea[*tile]))
+            .collect();
+        let road_positions: Vec<_> = road_buildings
+            .iter()
+            .map(|[start, end]| (corners[*start].position() + corners[*end].position()) / 2.)
+            .collect();
+        let road_orientations = road_buildings
+            .iter()
+            .map(|[start, end]| {
+                let (start, end) = (corners[*start], corners[*end]);
+
+                if start.column == end.column {
+                    RoadOrientation::Vert
+                } else if (end.y - start.y) * (end.column - start.column) > 0 {
+                    RoadOrientation::Inc
+                } else {
+                    RoadOrientation::Dec
+                }
+            })
+            .collect();
+
+        let mut building_roads = vec![Vec::new(); corners.len()];
+        let mut building_buildings = vec![Vec::new(); corners.len()];
+        for (road, [start, end]) in road_buildings.iter().enumerate() {
+            building_roads[*start].push(road);
+            building_roads[*end].push(road);
+            building_buildings[*start].push(*end);
+            building_buildings[*end].push(*start);
+        }
+
+        // Harbors, clockwise around the board, starting from the left, unless the board picked them.
+        // The coast is wherever land meets sea, or the edge of the board.
+        let harbor_positions = match harbors {
+            Some(harbors) => harbors
+                .iter()
+                .map(|[land, sea]| {
+                    land.position() + (sea.position() - land.position()) / 2. * HARBOR_DISTANCE
+                })
+                .collect(),
+            None => {
+                let center = tile_positions.iter().sum::<Vec2>() / tile_positions.len() as f32;
+                let mut harbor_positions: Vec<_> = edges
+                    .iter()
+                    .zip(road_positions.iter())
+                    .filter_map(|((_, tiles), road)| {
+                        let mut land = tiles.iter().filter(|tile| !tile_sea[**tile]);
+
+                        match (land.next(), land.next()) {
+                            (Some(tile), None) => Some(
+                                tile_positions[*tile]
+                                    + (*road - tile_positions[*tile]) * HARBOR_DISTANCE,
+                            ),
+                            _ => None,
+                        }
+                    })
+                    .collect();
+                harbor_positions.sort_by(|left, right| {
+                    let angle = |position: &Vec2| {
+                        let offset = *position - center;
+                        (TAU / 2. - offset.y.atan2(offset.x)).rem_euclid(TAU)
+                    };
+                    angle(left).total_cmp(&angle(right))
+                });
+
+                harbor_positions
+            }
+        };
+
+        // A harbor is on an edge, so the corners at either end of it are the only ones this close
+        let building_harbors = corners
+            .iter()
+            .map(|corner| {
+                harbor_positions.iter().position(|harbor: &Vec2| {
+                    harbor.distance(corner.position()) < CORNER_HEIGHT as f32
+                })
+            })
+            .collect();
+
+        Self {
+            tile_positions,
+            harbor_positions,
+            building_harbors,
+            road_positions,
+            road_orientations,
+            road_buildings,
+            building_positions: corners.iter().map(|corner| corner.position()).collect(),
+            building_tiles,
+            building_roads,
+            building_buildings,
+            road_land,
+            road_sea,
+            building_islands,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preset::BoardPreset;
+
+    fn layout(file: &str) -> Layout {
+        let preset: BoardPreset = ron::from_str(file).unwrap();
+        Layout::new(&preset.hexes, preset.harbor_edges().as_deref())
+    }
+
+    #[test]
+    fn standard_board_is_consistent() {
+        let layout = layout(include_str!("../assets/boards/standard.ron"));
+
+        assert_eq!(layout.building_positions.len(), 54);
+        assert_eq!(layout.road_positions.len(), 72);
+
+        for (building, others) in layout.building_buildings.iter().enumerate() {
+            for other in others {
+                assert!(layout.building_buildings[*other].contains(&building));
+            }
+        }
+
+        for (road, buildings) in layout.road_buildings.iter().enumerate() {
+            for building in buildings {
+                assert!(layout.building_roads[*building].contains(&road));
+            }
+        }
+
+        for roads in &layout.building_roads {
+            assert!((2..=3).contains(&roads.len()));
+        }
+    }
+
+    #[test]
+    fn extension_board_harbors_have_two_corners() {
+        let layout = layout(include_str!("../assets/boards/extension.ron"));
+
+        assert_eq!(layout.harbor_positions.len(), 11);
+
+        for harbor in 0..layout.harbor_positions.len() {
+            let corners = layout
+                .building_harbors
+                .iter()
+                .filter(|other| **other == Some(harbor))
+                .count();
+            assert_eq!(corners, 2);
+        }
+    }
+}
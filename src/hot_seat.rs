@@ -0,0 +1,169 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use crate::{
+    color::PlayerColor,
+    menu::{in_game, GameSetup, SetupMode},
+    turn::{Players, Turn},
+    ui::{UiRoot, BUTTON_FONT_SIZE, UI_BACKGROUND_COLOR},
+};
+
+pub struct HotSeatPlugin;
+
+impl Plugin for HotSeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HotSeat>()
+            .init_resource::<PassTo>()
+            .init_resource::<Viewer>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(in_game)
+                    .with_system(pass_device)
+                    .with_system(press_ready_button),
+            );
+    }
+}
+
+/// Whether the players are sharing one device.
+/// If so, only the active player's hand is face-up, and the board is hidden while the device is passed.
+#[derive(Default, Deref, DerefMut)]
+pub struct HotSeat(pub bool);
+
+/// The player that the device is being passed to, if the pass screen is up
+#[derive(Default, Deref, DerefMut)]
+pub struct PassTo(pub Option<PlayerColor>);
+
+/// The human player that has the device, if any has had it yet
+#[derive(Default, Deref, DerefMut)]
+pub struct Viewer(pub Option<PlayerColor>);
+
+/// Marks the screen that hides everything while the device is passed
+#[derive(Component)]
+pub struct PassScreen;
+
+#[derive(Component)]
+pub struct ReadyButton;
+
+const PASS_FONT_SIZE: f32 = 60.;
+
+/// When the device needs to go to another human player,
+/// put up a screen that hides everything until they're ready
+fn pass_device(
+    mut commands: Commands,
+    roots: Query<Entity, With<UiRoot>>,
+    hot_seat: Res<HotSeat>,
+    setup: Res<GameSetup>,
+    turn: Res<Turn>,
+    players: Res<Players>,
+    mut pass_to: ResMut<PassTo>,
+    mut viewer: ResMut<Viewer>,
+    assets: Res<AssetServer>,
+) {
+    if !**hot_seat || !turn.is_changed() {
+        return;
+    }
+
+    let player = match turn.player() {
+        Some(player) => players[player],
+        None => return,
+    };
+
+    // Bots don't need the device, nobody does while the game places the starting pieces,
+    // and the player might already have it
+    if setup.seat(player).bot
+        || (setup.setup_mode == SetupMode::Random && matches!(*turn, Turn::Setup { .. }))
+        || **viewer == Some(player)
+    {
+        return;
+    }
+
+    // Nobody has seen anything yet if this is the first human player
+    let passing = viewer.is_some();
+    **viewer = Some(player);
+
+    if !passing {
+        return;
+    }
+
+    **pass_to = Some(player);
+
+    let root = match roots.get_single() {
+        Ok(root) => root,
+        Err(_) => return,
+    };
+
+    commands.entity(root).with_children(|parent| {
+        parent
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        left: Val::Px(0.),
+                        bottom: Val::Px(0.),
+                        ..default()
+                    },
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::ColumnReverse,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                color: UI_BACKGROUND_COLOR.into(),
+                ..default()
+            })
+            .insert(PassScreen)
+            .with_children(|parent| {
+                // Pass text
+                parent.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        format!("Pass to {}", setup.seat(player).name),
+                        TextStyle {
+                            font: assets.load("FiraSans-Bold.ttf"),
+                            font_size: PASS_FONT_SIZE,
+                            color: player.into(),
+                        },
+                        default(),
+                    ),
+                    ..default()
+                });
+
+                // Ready button
+                parent
+                    .spawn_bundle(ButtonBundle::default())
+                    .insert(ReadyButton)
+                    .with_children(|parent| {
+                        // Button text
+                        parent.spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                "Ready",
+                                TextStyle {
+                                    font: assets.load("FiraSans-Bold.ttf"),
+                                    font_size: BUTTON_FONT_SIZE,
+                                    color: Color::BLACK,
+                                },
+                                default(),
+                            ),
+                            focus_policy: FocusPolicy::Pass,
+                            ..default()
+                        });
+                    });
+            });
+    });
+}
+
+/// Take down the pass screen when the player is ready
+fn press_ready_button(
+    mut commands: Commands,
+    buttons: Query<&Interaction, (With<ReadyButton>, Changed<Interaction>)>,
+    screens: Query<Entity, With<PassScreen>>,
+    mut pass_to: ResMut<PassTo>,
+) {
+    for interaction in buttons.iter() {
+        if let Interaction::Clicked = interaction {
+            for screen in screens.iter() {
+                commands.entity(screen).despawn_recursive();
+            }
+
+            **pass_to = None;
+        }
+    }
+}
@@ -1,13 +1,15 @@
 use bevy::{prelude::*, render::texture::DEFAULT_IMAGE_HANDLE};
 
 use crate::{
-    board::BoardIndex,
+    board::{Board, BoardIndex},
     building::{show_building_buttons, BuildingSlot},
-    button::{BuildingButton, RoadButton},
+    button::{BuildingButton, RoadButton, TileButton},
     chit::ChitSlot,
     harbor::HarborSlot,
+    knight::KnightSlot,
     road::RoadSlot,
     robber::RobberSlot,
+    ship::ShipSlot,
     tile::Tile,
 };
 
@@ -20,9 +22,12 @@ impl Plugin for ImagePlugin {
             .add_system(RobberSlot::update_images)
             .add_system(HarborSlot::update_images)
             .add_system(RoadSlot::update_images)
+            .add_system(ShipSlot::update_images)
             .add_system(BuildingSlot::update_images)
+            .add_system(KnightSlot::update_images)
             .add_system(BuildingButton::add_image.after(show_building_buttons))
-            .add_system(RoadButton::add_image);
+            .add_system(RoadButton::add_image)
+            .add_system(TileButton::add_image);
     }
 }
 
@@ -31,16 +36,23 @@ impl Plugin for ImagePlugin {
 /// Remember to add `update_images` to the app!
 pub trait UpdateImages: Component + Copy + Sized {
     /// Path to your image asset, if you have one
-    fn image(self, index: usize) -> Option<&'static str>;
+    fn image(self, board: &Board, index: usize) -> Option<&'static str>;
 
     /// Remember to add me to the app!
     fn update_images(
         mut commands: Commands,
         query: Query<(Entity, &Self, &BoardIndex, &Transform), Changed<Self>>,
+        board: Option<Res<Board>>,
         assets: Res<AssetServer>,
     ) {
+        // Board items only exist along with the board
+        let board = match board {
+            Some(board) => board,
+            None => return,
+        };
+
         for (entity, component, index, transform) in query.iter() {
-            let image = component.image(**index);
+            let image = component.image(&board, **index);
 
             // The entity is allowed to not already have a `SpriteBundle`, so we use `Commands` to add/overwrite it
             commands.entity(entity).insert_bundle(SpriteBundle {
@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::{Building, BuildingSlot, BuildingType},
+    button::{pressed, Clicked, MaybePressed},
+    color::{PlayerColor, COLOR_COUNT},
+    commodity::{Commodities, Commodity, COMMODITIES, COMMODITY_COUNT},
+    menu::in_game,
+    resource::Hands,
+    turn::{Players, Turn},
+    ui::{ChoiceBar, ImproveCityButton, ImprovementUi},
+    undo::{Action, Placement, UndoStack},
+};
+
+pub struct ImprovementPlugin;
+
+impl Plugin for ImprovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Improvements>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(press_improve_city_button)
+                .with_system(update_improvement_buttons)
+                .with_system(press_improvement_button)
+                .with_system(update_improvement_ui),
+        );
+    }
+}
+
+pub const MAX_IMPROVEMENT: u8 = 5;
+
+/// How far each player is along each city improvement track.
+/// Indexed by a `PlayerColor as usize`, and then by the `Commodity as usize` that pays for the track.
+#[derive(Default, Deref, DerefMut)]
+pub struct Improvements([[u8; COMMODITY_COUNT]; COLOR_COUNT]);
+
+impl Improvements {
+    /// How many commodities the player's next improvement on this track costs, if they can make it.
+    /// Each level costs one more than the last.
+    fn cost(&self, color: PlayerColor, commodity: Commodity) -> Option<u8> {
+        let level = self[color as usize][commodity as usize];
+
+        (level < MAX_IMPROVEMENT).then_some(level + 1)
+    }
+}
+
+/// A button to improve cities along the track that the commodity pays for
+#[derive(Component, Deref)]
+pub struct ImprovementButton(Commodity);
+
+const IMPROVEMENT_SIZE: Val = Val::Px(48.);
+
+/// Whether the player has a city, and can afford an improvement
+pub fn can_improve_city(
+    color: PlayerColor,
+    buildings: &Query<&BuildingSlot>,
+    commodities: &Commodities,
+    improvements: &Improvements,
+) -> bool {
+    let has_city = buildings.iter().any(|building| {
+        matches!(
+            **building,
+            Some(Building {
+                building_type: BuildingType::City,
+                color: other,
+            }) if other == color
+        )
+    });
+    let can_afford = COMMODITIES.into_iter().any(|commodity| {
+        matches!(improvements.cost(color, commodity), Some(cost)
+            if commodities[color as usize][commodity as usize] >= cost)
+    });
+
+    has_city && can_afford
+}
+
+/// Let the player pick a track, if they have a city, and can afford an improvement
+fn press_improve_city_button(
+    buttons: Query<&Interaction, (With<ImproveCityButton>, Changed<Interaction>)>,
+    buildings: Query<&BuildingSlot>,
+    players: Res<Players>,
+    commodities: Res<Commodities>,
+    improvements: Res<Improvements>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::Build { player, special } = *turn {
+        for interaction in buttons.iter() {
+            if let Interaction::Clicked = interaction {
+                if can_improve_city(players[player], &buildings, &commodities, &improvements) {
+                    *turn = Turn::ImproveCity { player, special };
+                }
+            }
+        }
+    }
+}
+
+/// Show a button for each track the player can afford while they're picking one, and hide them otherwise
+fn update_improvement_buttons(
+    mut commands: Commands,
+    bars: Query<Entity, With<ChoiceBar>>,
+    buttons: Query<Entity, With<ImprovementButton>>,
+    players: Res<Players>,
+    commodities: Res<Commodities>,
+    improvements: Res<Improvements>,
+    turn: Res<Turn>,
+    assets: Res<AssetServer>,
+) {
+    if !turn.is_changed() {
+        return;
+    }
+
+    for button in buttons.iter() {
+        commands.entity(button).despawn_recursive();
+    }
+
+    if let Turn::ImproveCity { player, .. } = *turn {
+        let color = players[player];
+
+        for bar in bars.iter() {
+            commands.entity(bar).with_children(|parent| {
+                for commodity in COMMODITIES {
+                    if matches!(improvements.cost(color, commodity), Some(cost)
+                        if commodities[color as usize][commodity as usize] >= cost)
+                    {
+                        parent
+                            .spawn_bundle(ButtonBundle {
+                                style: Style {
+                                    size: Size::new(IMPROVEMENT_SIZE, IMPROVEMENT_SIZE),
+                                    ..default()
+                                },
+                                image: assets.load(commodity.image()).into(),
+                                ..default()
+                            })
+                            .insert(ImprovementButton(commodity));
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Pay for the improvement the player picked
+fn press_improvement_button(
+    mut commands: Commands,
+    buttons: Query<(Entity, &Interaction, &ImprovementButton, Option<&Clicked>), MaybePressed>,
+    players: Res<Players>,
+    hands: Res<Hands>,
+    mut commodities: ResMut<Commodities>,
+    mut improvements: ResMut<Improvements>,
+    mut undo_stack: ResMut<UndoStack>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::ImproveCity { player, .. } = *turn {
+        for (button, interaction, commodity, clicked) in buttons.iter() {
+            if pressed(&mut commands, button, interaction, clicked) {
+                let color = players[player];
+
+                if let Some(cost) = improvements.cost(color, **commodity) {
+                    let count = &mut commodities[color as usize][**commodity as usize];
+
+                    if *count >= cost {
+                        undo_stack.push(Action {
+                            color,
+                            hand: hands[color as usize],
+                            placement: Some(Placement::Improvement {
+                                commodity: **commodity,
+                            }),
+                        });
+
+                        *count -= cost;
+                        improvements[color as usize][**commodity as usize] += 1;
+                        *turn = turn.next(players.len());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Show how far each player is along each track
+fn update_improvement_ui(
+    mut texts: Query<(&mut Text, &ImprovementUi)>,
+    improvements: Res<Improvements>,
+) {
+    if improvements.is_changed() {
+        for (mut text, ui) in texts.iter_mut() {
+            text.sections[0].value = COMMODITIES
+                .into_iter()
+                .map(|commodity| {
+                    format!(
+                        "{} {}",
+                        commodity.track(),
+                        improvements[ui.color as usize][commodity as usize]
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("  ");
+        }
+    }
+}
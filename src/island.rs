@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{Board, BoardIndex},
+    building::BuildingSlot,
+    color::COLOR_COUNT,
+    menu::{in_game, GameSetup},
+    preset::BoardPresets,
+    ui::PlayerHeading,
+};
+
+pub struct IslandPlugin;
+
+impl Plugin for IslandPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HomeIslands>()
+            .add_system(update_island_bonus.with_run_criteria(in_game));
+    }
+}
+
+/// The islands that each player settled in the setup phase.
+/// Indexed by a `PlayerColor as usize`.
+#[derive(Default, Deref, DerefMut)]
+pub struct HomeIslands([Vec<usize>; COLOR_COUNT]);
+
+/// Players get bonus victory points for each island they settle, other than their home islands.
+/// Show them next to the player's name.
+fn update_island_bonus(
+    changed_buildings: Query<(), Changed<BuildingSlot>>,
+    buildings: Query<(&BuildingSlot, &BoardIndex)>,
+    mut headings: Query<(&mut Text, &PlayerHeading)>,
+    board: Res<Board>,
+    home_islands: Res<HomeIslands>,
+    setup: Res<GameSetup>,
+    presets: Res<BoardPresets>,
+) {
+    if changed_buildings.is_empty() && !home_islands.is_changed() {
+        return;
+    }
+
+    let points = island_points(
+        &buildings,
+        &board,
+        &home_islands,
+        presets.get(&setup.board).island_bonus,
+    );
+
+    for (mut text, heading) in headings.iter_mut() {
+        let points = points[heading.color as usize];
+
+        text.sections[1].value = if points > 0 {
+            format!(" +{points} VP")
+        } else {
+            String::new()
+        };
+    }
+}
+
+/// The bonus victory points that each player has from settling islands.
+/// Indexed by a `PlayerColor as usize`.
+pub fn island_points(
+    buildings: &Query<(&BuildingSlot, &BoardIndex)>,
+    board: &Board,
+    home_islands: &HomeIslands,
+    bonus: u8,
+) -> [u8; COLOR_COUNT] {
+    let mut islands = [(); COLOR_COUNT].map(|_| Vec::new());
+
+    for (building, index) in buildings.iter() {
+        if let (Some(building), Some(island)) = (**building, board.layout.building_islands[**index])
+        {
+            let islands = &mut islands[building.color as usize];
+
+            if !home_islands[building.color as usize].contains(&island)
+                && !islands.contains(&island)
+            {
+                islands.push(island);
+            }
+        }
+    }
+
+    islands.map(|islands| islands.len() as u8 * bonus)
+}
@@ -0,0 +1,279 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{Board, BoardIndex},
+    building::BuildingSlot,
+    button::{BuildingButton, Clicked},
+    color::PlayerColor,
+    commodity::Commodity,
+    image::UpdateImages,
+    improvement::Improvements,
+    menu::in_game,
+    resource::Hands,
+    road::RoadSlot,
+    rules::{Cost, Rules},
+    ship::ShipSlot,
+    turn::{Players, Turn},
+    ui::{ActivateKnightButton, BuildKnightButton, PromoteKnightButton},
+    undo::{Action, Placement, UndoStack},
+};
+
+pub struct KnightPlugin;
+
+impl Plugin for KnightPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(show_knight_buttons)
+                .with_system(use_knight),
+        );
+    }
+}
+
+const MAX_KNIGHT_LEVEL: u8 = 3;
+/// Knights can only be promoted to the highest level with this much Politics
+const MIGHTY_KNIGHT_POLITICS: u8 = 3;
+
+/// Knights defend the island from the barbarians, but only while they're active
+#[derive(Clone, Copy)]
+pub struct Knight {
+    pub color: PlayerColor,
+    /// 1 to 3. How much the knight adds to the island's defense.
+    pub level: u8,
+    pub active: bool,
+}
+
+/// What the player is doing with a knight
+#[derive(Clone, Copy, PartialEq)]
+pub enum KnightAction {
+    Build,
+    Activate,
+    Promote,
+}
+
+/// Knights go on the same corners as buildings. A corner can't have both a building and a knight.
+#[derive(Clone, Component, Copy, Deref, DerefMut)]
+pub struct KnightSlot(pub Option<Knight>);
+
+impl UpdateImages for KnightSlot {
+    fn image(self, _: &Board, _: usize) -> Option<&'static str> {
+        self.map(|knight| {
+            // By level, and then by whether the knight is active
+            let images = match knight.color {
+                PlayerColor::Blue => [
+                    ["blue_knight_1.png", "blue_knight_1_active.png"],
+                    ["blue_knight_2.png", "blue_knight_2_active.png"],
+                    ["blue_knight_3.png", "blue_knight_3_active.png"],
+                ],
+                PlayerColor::Orange => [
+                    ["orange_knight_1.png", "orange_knight_1_active.png"],
+                    ["orange_knight_2.png", "orange_knight_2_active.png"],
+                    ["orange_knight_3.png", "orange_knight_3_active.png"],
+                ],
+                PlayerColor::Red => [
+                    ["red_knight_1.png", "red_knight_1_active.png"],
+                    ["red_knight_2.png", "red_knight_2_active.png"],
+                    ["red_knight_3.png", "red_knight_3_active.png"],
+                ],
+                PlayerColor::White => [
+                    ["white_knight_1.png", "white_knight_1_active.png"],
+                    ["white_knight_2.png", "white_knight_2_active.png"],
+                    ["white_knight_3.png", "white_knight_3_active.png"],
+                ],
+                PlayerColor::Green => [
+                    ["green_knight_1.png", "green_knight_1_active.png"],
+                    ["green_knight_2.png", "green_knight_2_active.png"],
+                    ["green_knight_3.png", "green_knight_3_active.png"],
+                ],
+                PlayerColor::Brown => [
+                    ["brown_knight_1.png", "brown_knight_1_active.png"],
+                    ["brown_knight_2.png", "brown_knight_2_active.png"],
+                    ["brown_knight_3.png", "brown_knight_3_active.png"],
+                ],
+            };
+
+            images[knight.level as usize - 1][knight.active as usize]
+        })
+    }
+}
+
+impl KnightAction {
+    pub fn cost(self, rules: &Rules) -> Cost {
+        match self {
+            Self::Build => rules.knight_cost,
+            Self::Activate => rules.activate_knight_cost,
+            Self::Promote => rules.promote_knight_cost,
+        }
+    }
+}
+
+/// How many of the player's knights are at the given level
+fn knight_count(color: PlayerColor, level: u8, knights: &[Option<Knight>]) -> usize {
+    knights
+        .iter()
+        .flatten()
+        .filter(|knight| knight.color == color && knight.level == level)
+        .count()
+}
+
+/// Whether the player may take the action at this corner.
+/// `politics` is how far the player is along the politics track.
+pub fn can_use_knight(
+    action: KnightAction,
+    index: usize,
+    color: PlayerColor,
+    politics: u8,
+    board: &Board,
+    buildings: &Query<&BuildingSlot>,
+    roads: &Query<&RoadSlot>,
+    ships: &Query<&ShipSlot>,
+    knights: &[Option<Knight>],
+    rules: &Rules,
+) -> bool {
+    match (action, knights[index]) {
+        // A new knight goes on an empty corner, next to the player's road or ship
+        (KnightAction::Build, None) => {
+            board.layout.building_islands[index].is_some()
+                && buildings.get(board.buildings[index]).unwrap().is_none()
+                && knight_count(color, 1, knights) < rules.knights_per_level
+                && board.layout.building_roads[index].iter().any(|road| {
+                    matches!(**roads.get(board.roads[*road]).unwrap(), Some(road) if road.color == color)
+                        || matches!(**ships.get(board.ships[*road]).unwrap(), Some(ship) if ship.color == color)
+                })
+        }
+        (KnightAction::Activate, Some(knight)) => knight.color == color && !knight.active,
+        (KnightAction::Promote, Some(knight)) => {
+            knight.color == color
+                && knight.level < MAX_KNIGHT_LEVEL
+                && (knight.level + 1 < MAX_KNIGHT_LEVEL || politics >= MIGHTY_KNIGHT_POLITICS)
+                && knight_count(color, knight.level + 1, knights) < rules.knights_per_level
+        }
+        _ => false,
+    }
+}
+
+/// Show the buttons for building, activating, or promoting a knight,
+/// when the player presses the button for it, and has enough resources
+fn show_knight_buttons(
+    mut buttons: Query<(&mut Visibility, &BoardIndex), With<BuildingButton>>,
+    build_buttons: Query<&Interaction, (With<BuildKnightButton>, Changed<Interaction>)>,
+    activate_buttons: Query<&Interaction, (With<ActivateKnightButton>, Changed<Interaction>)>,
+    promote_buttons: Query<&Interaction, (With<PromoteKnightButton>, Changed<Interaction>)>,
+    buildings: Query<&BuildingSlot>,
+    roads: Query<&RoadSlot>,
+    ships: Query<&ShipSlot>,
+    knights: Query<&KnightSlot>,
+    board: Res<Board>,
+    players: Res<Players>,
+    hands: Res<Hands>,
+    improvements: Res<Improvements>,
+    rules: Res<Rules>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::Build { player, special } = *turn {
+        let clicked =
+            |interaction: Option<&Interaction>| matches!(interaction, Some(Interaction::Clicked));
+        let action = if clicked(build_buttons.get_single().ok()) {
+            KnightAction::Build
+        } else if clicked(activate_buttons.get_single().ok()) {
+            KnightAction::Activate
+        } else if clicked(promote_buttons.get_single().ok()) {
+            KnightAction::Promote
+        } else {
+            return;
+        };
+
+        let color = players[player];
+
+        if !action.cost(&rules).can_afford(hands[color as usize]) {
+            return;
+        }
+
+        // Every corner's knight, by board index
+        let knights: Vec<_> = board
+            .knights
+            .iter()
+            .map(|knight| **knights.get(*knight).unwrap())
+            .collect();
+        let politics = improvements[color as usize][Commodity::Coin as usize];
+        let mut can_use = false;
+
+        for (mut visibility, index) in buttons.iter_mut() {
+            let visible = can_use_knight(
+                action, **index, color, politics, &board, &buildings, &roads, &ships, &knights,
+                &rules,
+            );
+
+            visibility.is_visible = visible;
+            can_use |= visible;
+        }
+
+        // They're charged once they pick a spot
+        if can_use {
+            *turn = Turn::Knight {
+                player,
+                special,
+                action,
+            };
+        }
+    }
+}
+
+/// Build, activate, or promote the knight that the player picked
+fn use_knight(
+    mut commands: Commands,
+    clicked_buttons: Query<(Entity, &BoardIndex), (With<BuildingButton>, With<Clicked>)>,
+    mut buttons: Query<&mut Visibility, With<BuildingButton>>,
+    mut knights: Query<&mut KnightSlot>,
+    board: Res<Board>,
+    players: Res<Players>,
+    rules: Res<Rules>,
+    mut turn: ResMut<Turn>,
+    mut hands: ResMut<Hands>,
+    mut undo_stack: ResMut<UndoStack>,
+) {
+    if let Turn::Knight { player, action, .. } = *turn {
+        for (entity, index) in clicked_buttons.iter() {
+            commands.entity(entity).remove::<Clicked>();
+
+            let color = players[player];
+            let hand = &mut hands[color as usize];
+            let mut knight = knights.get_mut(board.knights[**index]).unwrap();
+
+            undo_stack.push(Action {
+                color,
+                hand: *hand,
+                placement: Some(Placement::Knight {
+                    index: **index,
+                    previous: **knight,
+                }),
+            });
+
+            action.cost(&rules).pay(hand);
+
+            **knight = match (action, **knight) {
+                (KnightAction::Build, _) => Some(Knight {
+                    color,
+                    level: 1,
+                    active: false,
+                }),
+                (KnightAction::Activate, Some(knight)) => Some(Knight {
+                    active: true,
+                    ..knight
+                }),
+                (KnightAction::Promote, Some(knight)) => Some(Knight {
+                    level: knight.level + 1,
+                    ..knight
+                }),
+                (_, None) => None,
+            };
+
+            for mut visibility in buttons.iter_mut() {
+                visibility.is_visible = false;
+            }
+
+            *turn = turn.next(players.len());
+        }
+    }
+}
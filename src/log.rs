@@ -0,0 +1,287 @@
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    asset::FileAssetIo,
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+
+use crate::{
+    color::PlayerColor,
+    commodity::{COMMODITIES, COMMODITY_COUNT},
+    menu::{in_game, GameSetup},
+    resource::{Resource, RESOURCES, RESOURCE_COUNT},
+    turn::Players,
+    ui::{ExportLogButton, LogFilterButton, LogText, LogUi},
+};
+
+pub struct LogPlugin;
+
+impl Plugin for LogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameLog>()
+            .init_resource::<LogView>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(in_game)
+                    .with_system(reset_log_view)
+                    .with_system(press_filter_button.after(reset_log_view))
+                    .with_system(scroll_log.after(reset_log_view))
+                    .with_system(update_log_text.after(press_filter_button).after(scroll_log))
+                    .with_system(export_log),
+            );
+    }
+}
+
+/// Something that happened in the game
+#[derive(Clone, Copy)]
+pub enum LogEvent {
+    Rolled {
+        dice: [u8; 2],
+    },
+    /// Cards from production
+    Received {
+        resources: [u8; RESOURCE_COUNT],
+        commodities: [u8; COMMODITY_COUNT],
+    },
+    /// A resource picked for gold
+    Chose {
+        resource: Resource,
+    },
+    /// A resource discarded on a 7
+    Discarded {
+        resource: Resource,
+    },
+    MovedRobber,
+    /// Which card was taken is only known to the two players
+    Stole {
+        victim: PlayerColor,
+    },
+}
+
+/// An event, and the player it happened to
+#[derive(Clone, Copy)]
+pub struct LogEntry {
+    pub color: PlayerColor,
+    pub event: LogEvent,
+}
+
+impl LogEntry {
+    fn text(self, setup: &GameSetup) -> String {
+        let name = &setup.seat(self.color).name;
+
+        match self.event {
+            LogEvent::Rolled {
+                dice: [die_1, die_2],
+            } => {
+                format!("{name} rolled {} ({die_1} + {die_2})", die_1 + die_2)
+            }
+            LogEvent::Received {
+                resources,
+                commodities,
+            } => {
+                let cards: Vec<_> = RESOURCES
+                    .into_iter()
+                    .map(|resource| (resources[resource as usize], resource.name()))
+                    .chain(
+                        COMMODITIES
+                            .into_iter()
+                            .map(|commodity| (commodities[commodity as usize], commodity.name())),
+                    )
+                    .filter(|(count, _)| *count > 0)
+                    .map(|(count, name)| format!("{count} {name}"))
+                    .collect();
+
+                format!("{name} received {}", cards.join(", "))
+            }
+            LogEvent::Chose { resource } => format!("{name} took {} for gold", resource.name()),
+            LogEvent::Discarded { resource } => format!("{name} discarded {}", resource.name()),
+            LogEvent::MovedRobber => format!("{name} moved the robber"),
+            LogEvent::Stole { victim } => {
+                format!("{name} stole a card from {}", setup.seat(victim).name)
+            }
+        }
+    }
+}
+
+/// Everything that's happened this game, oldest first
+#[derive(Default, Deref)]
+pub struct GameLog(Vec<LogEntry>);
+
+impl GameLog {
+    pub fn push(&mut self, color: PlayerColor, event: LogEvent) {
+        self.0.push(LogEntry { color, event });
+    }
+}
+
+/// What part of the log is in the UI
+#[derive(Default)]
+struct LogView {
+    /// Only this player's entries are shown, if there is one
+    filter: Option<PlayerColor>,
+    /// How many lines up from the newest entry the log is scrolled
+    scroll: usize,
+}
+
+/// How many entries fit in the log panel at once
+const LOG_LINES: usize = 12;
+
+/// The entries that pass the filter
+fn filtered(log: &GameLog, view: &LogView) -> Vec<LogEntry> {
+    log.iter()
+        .copied()
+        .filter(|entry| view.filter.is_none() || view.filter == Some(entry.color))
+        .collect()
+}
+
+/// Go back to showing everyone's newest entries when a game starts
+fn reset_log_view(log: Res<GameLog>, mut view: ResMut<LogView>) {
+    if log.is_added() {
+        *view = LogView::default();
+    }
+}
+
+/// Cycle through showing everyone, and each player in turn order
+fn press_filter_button(
+    buttons: Query<(&Interaction, &Children), (With<LogFilterButton>, Changed<Interaction>)>,
+    mut texts: Query<&mut Text>,
+    players: Res<Players>,
+    setup: Res<GameSetup>,
+    mut view: ResMut<LogView>,
+) {
+    for (interaction, children) in buttons.iter() {
+        if let Interaction::Clicked = interaction {
+            view.filter = match view.filter {
+                None => players.first().copied(),
+                Some(color) => players
+                    .iter()
+                    .skip_while(|other| **other != color)
+                    .nth(1)
+                    .copied(),
+            };
+            view.scroll = 0;
+
+            for child in children.iter() {
+                if let Ok(mut text) = texts.get_mut(*child) {
+                    text.sections[0].value = match view.filter {
+                        Some(color) => setup.seat(color).name.clone(),
+                        None => "Everyone".to_string(),
+                    };
+                }
+            }
+        }
+    }
+}
+
+/// Scroll through older entries with the mouse wheel, while the cursor is over the log
+fn scroll_log(
+    logs: Query<&Interaction, With<LogUi>>,
+    mut wheels: EventReader<MouseWheel>,
+    log: Res<GameLog>,
+    mut view: ResMut<LogView>,
+) {
+    let lines: f32 = wheels
+        .iter()
+        .map(|wheel| match wheel.unit {
+            MouseScrollUnit::Line => wheel.y,
+            MouseScrollUnit::Pixel => wheel.y.signum(),
+        })
+        .sum();
+
+    if lines == 0.
+        || !logs
+            .iter()
+            .any(|interaction| matches!(interaction, Interaction::Hovered))
+    {
+        return;
+    }
+
+    let max_scroll = filtered(&log, &view).len().saturating_sub(LOG_LINES);
+    let scroll = (view.scroll as f32 + lines)
+        .round()
+        .clamp(0., max_scroll as f32) as usize;
+
+    if scroll != view.scroll {
+        view.scroll = scroll;
+    }
+}
+
+/// Show the entries that are scrolled to, in the color of the player each one is about
+fn update_log_text(
+    mut texts: Query<&mut Text, With<LogText>>,
+    log: Res<GameLog>,
+    view: Res<LogView>,
+    setup: Res<GameSetup>,
+) {
+    if !log.is_changed() && !view.is_changed() {
+        return;
+    }
+
+    let entries = filtered(&log, &view);
+    let end = entries.len().saturating_sub(view.scroll);
+    let start = end.saturating_sub(LOG_LINES);
+
+    for mut text in texts.iter_mut() {
+        let style = text.sections[0].style.clone();
+
+        // There's always a section, so the style is kept
+        text.sections = entries[start..end]
+            .iter()
+            .map(|entry| TextSection {
+                value: format!("{}\n", entry.text(&setup)),
+                style: TextStyle {
+                    color: entry.color.into(),
+                    ..style.clone()
+                },
+            })
+            .collect();
+
+        if text.sections.is_empty() {
+            text.sections.push(TextSection {
+                value: String::new(),
+                style,
+            });
+        }
+    }
+}
+
+/// Save the whole log to a text file, next to the assets folder, and say how it went on the button
+fn export_log(
+    buttons: Query<(&Interaction, &Children), (With<ExportLogButton>, Changed<Interaction>)>,
+    mut texts: Query<&mut Text>,
+    log: Res<GameLog>,
+    setup: Res<GameSetup>,
+) {
+    for (interaction, children) in buttons.iter() {
+        if let Interaction::Clicked = interaction {
+            let time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs());
+            let path = FileAssetIo::get_root_path().join(format!("log_{time}.txt"));
+            let contents: String = log
+                .iter()
+                .map(|entry| format!("{}\n", entry.text(&setup)))
+                .collect();
+
+            let result = match fs::write(&path, contents) {
+                Ok(()) => {
+                    info!("saved the game log to {path:?}");
+                    "Saved"
+                }
+                Err(error) => {
+                    error!("couldn't save the game log to {path:?}: {error}");
+                    "Couldn't save"
+                }
+            };
+
+            for child in children.iter() {
+                if let Ok(mut text) = texts.get_mut(*child) {
+                    text.sections[0].value = result.to_string();
+                }
+            }
+        }
+    }
+}
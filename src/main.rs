@@ -1,39 +1,80 @@
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
+use barbarian::BarbarianPlugin;
 use bevy::prelude::*;
 use board::BoardPlugin;
+use bot::BotPlugin;
 use building::BuildingPlugin;
 use button::ButtonPlugin;
 use camera::CameraPlugin;
+use cost_card::CostCardPlugin;
 use cursor::CursorPlugin;
+use discard::DiscardPlugin;
+use draft::DraftPlugin;
+use event_deck::EventDeckPlugin;
+use ghost::GhostPlugin;
+use gold::GoldPlugin;
+use hot_seat::HotSeatPlugin;
 use image::ImagePlugin;
+use improvement::ImprovementPlugin;
+use island::IslandPlugin;
+use knight::KnightPlugin;
+use log::LogPlugin;
+use menu::MenuPlugin;
+use overlay::OverlayPlugin;
 use resource::ResourcePlugin;
 use road::RoadPlugin;
+use robber::RobberPlugin;
+use roll::RollPlugin;
+use rules::RulesPlugin;
+use ship::ShipPlugin;
 use status::StatusPlugin;
 use turn::TurnPlugin;
 use ui::UiPlugin;
+use undo::UndoPlugin;
 
 // The greatest project structure known to man /s
-mod array;
+mod barbarian;
 mod board;
+mod bot;
 mod building;
 mod button;
 mod camera;
 mod chit;
 mod color;
+mod commodity;
+mod cost_card;
 mod cursor;
 mod development_card;
+mod discard;
+mod draft;
+mod event_deck;
+mod ghost;
+mod gold;
 mod harbor;
+mod hex;
+mod hot_seat;
 mod image;
+mod improvement;
+mod island;
+mod knight;
+mod log;
 mod math;
+mod menu;
+mod overlay;
+mod preset;
 mod random;
 mod resource;
 mod road;
 mod robber;
+mod roll;
+mod rules;
+mod ship;
 mod status;
 mod tile;
 mod turn;
 mod ui;
+mod undo;
 
 // Completely original title
 static TITLE: &str = "Cotton";
@@ -46,17 +87,37 @@ fn main() {
             ..default()
         })
         .add_plugins(DefaultPlugins)
+        .add_plugin(BarbarianPlugin)
         .add_plugin(BoardPlugin)
+        .add_plugin(BotPlugin)
         .add_plugin(BuildingPlugin)
         .add_plugin(ButtonPlugin)
         .add_plugin(CameraPlugin)
+        .add_plugin(CostCardPlugin)
         .add_plugin(CursorPlugin)
+        .add_plugin(DiscardPlugin)
+        .add_plugin(DraftPlugin)
+        .add_plugin(EventDeckPlugin)
+        .add_plugin(GhostPlugin)
+        .add_plugin(GoldPlugin)
+        .add_plugin(HotSeatPlugin)
         .add_plugin(ImagePlugin)
+        .add_plugin(ImprovementPlugin)
+        .add_plugin(IslandPlugin)
+        .add_plugin(KnightPlugin)
+        .add_plugin(LogPlugin)
+        .add_plugin(MenuPlugin)
+        .add_plugin(OverlayPlugin)
         .add_plugin(ResourcePlugin)
         .add_plugin(RoadPlugin)
+        .add_plugin(RobberPlugin)
+        .add_plugin(RollPlugin)
+        .add_plugin(RulesPlugin)
+        .add_plugin(ShipPlugin)
         .add_plugin(StatusPlugin)
         .add_plugin(TurnPlugin)
         .add_plugin(UiPlugin)
+        .add_plugin(UndoPlugin)
         .insert_resource(ClearColor(CLEAR_COLOR))
         .run();
 }
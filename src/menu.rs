@@ -0,0 +1,736 @@
+use bevy::{app::AppExit, ecs::schedule::ShouldRun, prelude::*, ui::FocusPolicy};
+
+use crate::{
+    barbarian::Barbarians,
+    board::Board,
+    color::{PlayerColor, COLORS, COLOR_COUNT},
+    commodity::Commodities,
+    discard::Discards,
+    draft::Draft,
+    event_deck::EventDeck,
+    gold::GoldChoices,
+    hot_seat::{HotSeat, PassTo, Viewer},
+    improvement::Improvements,
+    island::HomeIslands,
+    log::GameLog,
+    preset::{BoardPresets, DEFAULT_BOARD},
+    resource::Hands,
+    roll::Roll,
+    rules::Rules,
+    ship::ShipsThisTurn,
+    turn::{Players, Turn},
+    ui::{QuitButton, RestartButton, UiRoot, BUTTON_FONT_SIZE, UI_BACKGROUND_COLOR},
+    undo::UndoStack,
+    TITLE,
+};
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Screen>()
+            .init_resource::<GameSetup>()
+            .init_resource::<Focus>()
+            .add_system(teardown)
+            .add_system(start_game.with_run_criteria(starting_game).after(teardown))
+            .add_system(update_menu.after(teardown))
+            // Screen changes should be seen by everything else in the same frame
+            .add_system(press_menu_button.before(teardown))
+            .add_system(type_text)
+            .add_system(
+                press_game_buttons
+                    .with_run_criteria(in_game)
+                    .before(teardown),
+            );
+    }
+}
+
+/// Which screen we're on
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Screen {
+    #[default]
+    Menu,
+    Game,
+}
+
+/// Who's playing as a given color
+#[derive(Clone)]
+pub struct Seat {
+    pub color: PlayerColor,
+    pub name: String,
+    pub bot: bool,
+}
+
+impl Seat {
+    fn new(color: PlayerColor) -> Self {
+        Self {
+            color,
+            name: color.into(),
+            bot: false,
+        }
+    }
+}
+
+/// Which rules the game is played by
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Ruleset {
+    #[default]
+    Base,
+    /// Cities make commodities, which pay for city improvements,
+    /// and knights defend the island from the barbarians
+    CitiesAndKnights,
+}
+
+impl Ruleset {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Base => "Base",
+            Self::CitiesAndKnights => "Cities & Knights",
+        }
+    }
+}
+
+/// How the starting settlements and roads are placed
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum SetupMode {
+    /// Everyone places their own, in the order from the rules file
+    #[default]
+    Standard,
+    /// The game places everyone's at random
+    Random,
+    /// In a 3-player game, everyone places a third settlement and road, in turn order
+    ThirdSettlement,
+    /// Before placing, players take turns picking their place in the turn order, in a random order.
+    /// Nobody bids or pays for their place.
+    Draft,
+}
+
+impl SetupMode {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::Random => "Random",
+            Self::ThirdSettlement => "Third settlement (3 players)",
+            Self::Draft => "Pick turn order",
+        }
+    }
+}
+
+/// Everything chosen in the menu before starting a game
+pub struct GameSetup {
+    pub seats: Vec<Seat>,
+    pub ruleset: Ruleset,
+    pub setup_mode: SetupMode,
+    /// File name of the board preset, without the extension
+    pub board: String,
+    /// Digits typed in the menu. If empty, the board is random.
+    pub seed: String,
+    pub hot_seat: bool,
+    /// Draw from the event deck instead of rolling dice
+    pub event_deck: bool,
+    /// Players with few victory points are safe from the robber
+    pub friendly_robber: bool,
+}
+
+impl Default for GameSetup {
+    fn default() -> Self {
+        Self {
+            seats: COLORS[..DEFAULT_PLAYERS]
+                .iter()
+                .copied()
+                .map(Seat::new)
+                .collect(),
+            ruleset: default(),
+            setup_mode: default(),
+            board: DEFAULT_BOARD.to_string(),
+            seed: String::new(),
+            hot_seat: false,
+            event_deck: false,
+            friendly_robber: false,
+        }
+    }
+}
+
+impl GameSetup {
+    /// Gets the seat of the player with the given color. Panics if nobody is playing that color.
+    pub fn seat(&self, color: PlayerColor) -> &Seat {
+        self.seats.iter().find(|seat| seat.color == color).unwrap()
+    }
+
+    /// The seed to generate the board with, if there is one
+    pub fn seed(&self) -> Option<u64> {
+        self.seed.parse().ok()
+    }
+
+    /// Gets the first color after `color` that nobody is playing, if there is one
+    fn free_color_after(&self, color: Option<PlayerColor>) -> Option<PlayerColor> {
+        let start = color.map_or(0, |color| color as usize + 1);
+
+        (0..COLOR_COUNT)
+            .map(|offset| COLORS[(start + offset) % COLOR_COUNT])
+            .find(|&color| self.seats.iter().all(|seat| seat.color != color))
+    }
+}
+
+const MIN_PLAYERS: usize = 2;
+const DEFAULT_PLAYERS: usize = 4;
+const MAX_NAME_LENGTH: usize = 16;
+// Every 19-digit number fits in a `u64`
+const MAX_SEED_LENGTH: usize = 19;
+
+/// A text field in the menu
+#[derive(Clone, Copy, PartialEq)]
+enum TextField {
+    Name(usize),
+    Seed,
+}
+
+/// The text field that's being typed into, if any
+#[derive(Default, Deref, DerefMut)]
+struct Focus(Option<TextField>);
+
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Clone, Component, Copy)]
+enum MenuButton {
+    RemovePlayer,
+    AddPlayer,
+    Color(usize),
+    Name(usize),
+    Bot(usize),
+    Ruleset,
+    FriendlyRobber,
+    SetupMode,
+    Board,
+    Seed,
+    HotSeat,
+    EventDeck,
+    Start,
+    Quit,
+}
+
+const TITLE_FONT_SIZE: f32 = 80.;
+const MENU_MARGIN: f32 = 5.;
+const COLOR_BUTTON_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
+const FOCUSED_FIELD_COLOR: Color = Color::rgb(1., 1., 0.6);
+
+/// Run criteria for systems that need a game to be going on
+pub fn in_game(board: Option<Res<Board>>) -> ShouldRun {
+    if board.is_some() {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// Run criteria for systems that set up a new game
+pub fn starting_game(screen: Res<Screen>) -> ShouldRun {
+    if screen.is_changed() && *screen == Screen::Game {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// When the screen changes, get rid of the game, if there is one
+pub fn teardown(
+    mut commands: Commands,
+    roots: Query<Entity, With<UiRoot>>,
+    screen: Res<Screen>,
+    board: Option<Res<Board>>,
+) {
+    if screen.is_changed() {
+        for root in roots.iter() {
+            commands.entity(root).despawn_recursive();
+        }
+
+        if let Some(board) = board {
+            for entity in board.entities() {
+                commands.entity(entity).despawn();
+            }
+
+            commands.remove_resource::<Board>();
+        }
+    }
+}
+
+/// Reset the game's state for a new game
+pub fn start_game(
+    mut commands: Commands,
+    rules: Res<Rules>,
+    mut setup: ResMut<GameSetup>,
+    mut players: ResMut<Players>,
+) {
+    for seat in setup.seats.iter_mut() {
+        if seat.name.trim().is_empty() {
+            seat.name = seat.color.into();
+        }
+    }
+
+    // This is set directly, so the UI can be built from it this frame
+    *players = Players::shuffled(setup.seats.iter().map(|seat| seat.color).collect());
+
+    // These are set with `Commands`, so game systems see them change when the new board is ready
+    let setup_turn = Turn::Setup {
+        round: 1,
+        rounds: if setup.setup_mode == SetupMode::ThirdSettlement && players.len() == 3 {
+            3
+        } else {
+            2
+        },
+        player: 0,
+        road: false,
+        order: rules.setup_order,
+    };
+
+    if setup.setup_mode == SetupMode::Draft {
+        commands.insert_resource(Turn::Draft { player: 0 });
+        commands.insert_resource(Draft::new(players.len(), setup_turn));
+    } else {
+        commands.insert_resource(setup_turn);
+    }
+    commands.insert_resource(Hands::default());
+    commands.insert_resource(Roll::default());
+    commands.insert_resource(GameLog::default());
+    commands.insert_resource(HotSeat(setup.hot_seat));
+    commands.insert_resource(PassTo::default());
+    commands.insert_resource(Viewer::default());
+    commands.insert_resource(UndoStack::default());
+    commands.insert_resource(ShipsThisTurn::default());
+    commands.insert_resource(HomeIslands::default());
+    commands.insert_resource(GoldChoices::default());
+    commands.insert_resource(Discards::default());
+    commands.insert_resource(Commodities::default());
+    commands.insert_resource(Improvements::default());
+    commands.insert_resource(Barbarians::default());
+    commands.insert_resource(EventDeck::default());
+}
+
+/// Rebuild the menu whenever something in it changes
+fn update_menu(
+    mut commands: Commands,
+    roots: Query<Entity, With<MenuRoot>>,
+    screen: Res<Screen>,
+    setup: Res<GameSetup>,
+    focus: Res<Focus>,
+    presets: Res<BoardPresets>,
+    rules: Res<Rules>,
+    assets: Res<AssetServer>,
+) {
+    if !screen.is_changed() && !setup.is_changed() && !focus.is_changed() {
+        return;
+    }
+
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+
+    if *screen != Screen::Menu {
+        return;
+    }
+
+    let font = assets.load("FiraSans-Bold.ttf");
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: UI_BACKGROUND_COLOR.into(),
+            ..default()
+        })
+        .insert(MenuRoot)
+        .with_children(|parent| {
+            // Title
+            spawn_text(parent, TITLE, TITLE_FONT_SIZE, default(), &font);
+
+            // Player count
+            spawn_row(parent, |parent| {
+                spawn_text(parent, "Players", BUTTON_FONT_SIZE, default(), &font);
+                spawn_button(parent, "-", MenuButton::RemovePlayer, default(), &font);
+                spawn_text(
+                    parent,
+                    setup.seats.len().to_string(),
+                    BUTTON_FONT_SIZE,
+                    default(),
+                    &font,
+                );
+                spawn_button(parent, "+", MenuButton::AddPlayer, default(), &font);
+            });
+
+            // Seats
+            for (i, seat) in setup.seats.iter().enumerate() {
+                spawn_row(parent, |parent| {
+                    parent
+                        .spawn_bundle(ButtonBundle {
+                            style: Style {
+                                margin: Rect::all(Val::Px(MENU_MARGIN)),
+                                ..default()
+                            },
+                            color: COLOR_BUTTON_COLOR.into(),
+                            ..default()
+                        })
+                        .insert(MenuButton::Color(i))
+                        .with_children(|parent| {
+                            spawn_text(
+                                parent,
+                                seat.color,
+                                BUTTON_FONT_SIZE,
+                                seat.color.into(),
+                                &font,
+                            );
+                        });
+
+                    spawn_field(
+                        parent,
+                        &seat.name,
+                        "",
+                        **focus == Some(TextField::Name(i)),
+                        MenuButton::Name(i),
+                        &font,
+                    );
+
+                    spawn_button(
+                        parent,
+                        if seat.bot { "Bot" } else { "Human" },
+                        MenuButton::Bot(i),
+                        default(),
+                        &font,
+                    );
+                });
+            }
+
+            // Ruleset
+            spawn_row(parent, |parent| {
+                spawn_text(parent, "Rules", BUTTON_FONT_SIZE, default(), &font);
+                spawn_button(
+                    parent,
+                    setup.ruleset.name(),
+                    MenuButton::Ruleset,
+                    default(),
+                    &font,
+                );
+            });
+
+            // Friendly robber, with the threshold from the rules file
+            spawn_row(parent, |parent| {
+                spawn_text(
+                    parent,
+                    "Friendly robber",
+                    BUTTON_FONT_SIZE,
+                    default(),
+                    &font,
+                );
+                spawn_button(
+                    parent,
+                    if setup.friendly_robber {
+                        format!("Safe at {} VP or less", rules.friendly_robber_points)
+                    } else {
+                        "Off".to_string()
+                    },
+                    MenuButton::FriendlyRobber,
+                    default(),
+                    &font,
+                );
+            });
+
+            // Setup mode
+            spawn_row(parent, |parent| {
+                spawn_text(parent, "Setup", BUTTON_FONT_SIZE, default(), &font);
+                spawn_button(
+                    parent,
+                    setup.setup_mode.name(),
+                    MenuButton::SetupMode,
+                    default(),
+                    &font,
+                );
+            });
+
+            // Board preset
+            spawn_row(parent, |parent| {
+                spawn_text(parent, "Board", BUTTON_FONT_SIZE, default(), &font);
+                spawn_button(
+                    parent,
+                    &presets.get(&setup.board).name,
+                    MenuButton::Board,
+                    default(),
+                    &font,
+                );
+            });
+
+            // Board seed
+            spawn_row(parent, |parent| {
+                spawn_text(parent, "Seed", BUTTON_FONT_SIZE, default(), &font);
+                spawn_field(
+                    parent,
+                    &setup.seed,
+                    "Random",
+                    **focus == Some(TextField::Seed),
+                    MenuButton::Seed,
+                    &font,
+                );
+            });
+
+            // Hot-seat mode
+            spawn_row(parent, |parent| {
+                spawn_text(parent, "Hot-seat", BUTTON_FONT_SIZE, default(), &font);
+                spawn_button(
+                    parent,
+                    if setup.hot_seat { "On" } else { "Off" },
+                    MenuButton::HotSeat,
+                    default(),
+                    &font,
+                );
+            });
+
+            // Event deck
+            spawn_row(parent, |parent| {
+                spawn_text(parent, "Event deck", BUTTON_FONT_SIZE, default(), &font);
+                spawn_button(
+                    parent,
+                    if setup.event_deck { "On" } else { "Off" },
+                    MenuButton::EventDeck,
+                    default(),
+                    &font,
+                );
+            });
+
+            spawn_row(parent, |parent| {
+                spawn_button(parent, "Start", MenuButton::Start, default(), &font);
+                spawn_button(parent, "Quit", MenuButton::Quit, default(), &font);
+            });
+        });
+}
+
+/// Spawn a horizontal row of menu items
+fn spawn_row(parent: &mut ChildBuilder, children: impl FnOnce(&mut ChildBuilder)) {
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                margin: Rect::all(Val::Px(MENU_MARGIN)),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .with_children(children);
+}
+
+fn spawn_text(
+    parent: &mut ChildBuilder,
+    text: impl Into<String>,
+    font_size: f32,
+    color: Color,
+    font: &Handle<Font>,
+) {
+    parent.spawn_bundle(TextBundle {
+        style: Style {
+            margin: Rect::all(Val::Px(MENU_MARGIN)),
+            ..default()
+        },
+        text: Text::with_section(
+            text,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color,
+            },
+            default(),
+        ),
+        focus_policy: FocusPolicy::Pass,
+        ..default()
+    });
+}
+
+/// `background` is the button's color. `default()` is white.
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    text: impl Into<String>,
+    button: MenuButton,
+    background: Color,
+    font: &Handle<Font>,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                margin: Rect::all(Val::Px(MENU_MARGIN)),
+                ..default()
+            },
+            color: background.into(),
+            ..default()
+        })
+        .insert(button)
+        .with_children(|parent| {
+            spawn_text(parent, text, BUTTON_FONT_SIZE, Color::BLACK, font);
+        });
+}
+
+/// A button that can be clicked to type into it. `placeholder` is shown when it's empty and not focused.
+fn spawn_field(
+    parent: &mut ChildBuilder,
+    text: &str,
+    placeholder: &str,
+    focused: bool,
+    button: MenuButton,
+    font: &Handle<Font>,
+) {
+    spawn_button(
+        parent,
+        if focused {
+            format!("{text}_")
+        } else if text.is_empty() {
+            placeholder.to_string()
+        } else {
+            text.to_string()
+        },
+        button,
+        if focused {
+            FOCUSED_FIELD_COLOR
+        } else {
+            default()
+        },
+        font,
+    );
+}
+
+/// Handle clicks on the menu's buttons
+fn press_menu_button(
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut setup: ResMut<GameSetup>,
+    mut focus: ResMut<Focus>,
+    mut screen: ResMut<Screen>,
+    presets: Res<BoardPresets>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if let Interaction::Clicked = interaction {
+            // Clicking anything other than a text field stops typing
+            let mut new_focus = None;
+
+            match *button {
+                MenuButton::RemovePlayer => {
+                    if setup.seats.len() > MIN_PLAYERS {
+                        setup.seats.pop();
+                    }
+                }
+                MenuButton::AddPlayer => {
+                    if let Some(color) = setup.free_color_after(None) {
+                        setup.seats.push(Seat::new(color));
+
+                        // Move to a bigger board if everyone doesn't fit
+                        if presets.get(&setup.board).max_players < setup.seats.len() {
+                            setup.board =
+                                presets.after(&setup.board, setup.seats.len()).to_string();
+                        }
+                    }
+                }
+                MenuButton::Color(i) => {
+                    let old = setup.seats[i].color;
+
+                    if let Some(new) = setup.free_color_after(Some(old)) {
+                        let seat = &mut setup.seats[i];
+
+                        // Keep the name in sync, unless the player has typed their own
+                        if seat.name == String::from(old) {
+                            seat.name = new.into();
+                        }
+
+                        seat.color = new;
+                    }
+                }
+                MenuButton::Name(i) => new_focus = Some(TextField::Name(i)),
+                MenuButton::Bot(i) => setup.seats[i].bot ^= true,
+                MenuButton::Ruleset => {
+                    setup.ruleset = match setup.ruleset {
+                        Ruleset::Base => Ruleset::CitiesAndKnights,
+                        Ruleset::CitiesAndKnights => Ruleset::Base,
+                    }
+                }
+                MenuButton::FriendlyRobber => setup.friendly_robber ^= true,
+                MenuButton::SetupMode => {
+                    setup.setup_mode = match setup.setup_mode {
+                        SetupMode::Standard => SetupMode::Random,
+                        SetupMode::Random => SetupMode::ThirdSettlement,
+                        SetupMode::ThirdSettlement => SetupMode::Draft,
+                        SetupMode::Draft => SetupMode::Standard,
+                    }
+                }
+                MenuButton::Board => {
+                    setup.board = presets.after(&setup.board, setup.seats.len()).to_string();
+                }
+                MenuButton::Seed => new_focus = Some(TextField::Seed),
+                MenuButton::HotSeat => setup.hot_seat ^= true,
+                MenuButton::EventDeck => setup.event_deck ^= true,
+                MenuButton::Start => *screen = Screen::Game,
+                MenuButton::Quit => exit.send(AppExit),
+            }
+
+            if **focus != new_focus {
+                **focus = new_focus;
+            }
+        }
+    }
+}
+
+/// Type into the focused text field
+fn type_text(
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut focus: ResMut<Focus>,
+    mut setup: ResMut<GameSetup>,
+) {
+    let typed: Vec<_> = characters
+        .iter()
+        .map(|character| character.char)
+        .filter(|character| !character.is_control())
+        .collect();
+    let backspace = keys.just_pressed(KeyCode::Back);
+
+    if let Some(field) = **focus {
+        if keys.just_pressed(KeyCode::Return) || keys.just_pressed(KeyCode::Escape) {
+            **focus = None;
+        } else if backspace || !typed.is_empty() {
+            let (text, max_length, digits_only) = match field {
+                TextField::Name(i) => (&mut setup.seats[i].name, MAX_NAME_LENGTH, false),
+                TextField::Seed => (&mut setup.seed, MAX_SEED_LENGTH, true),
+            };
+
+            if backspace {
+                text.pop();
+            }
+
+            for character in typed {
+                if text.chars().count() < max_length && (!digits_only || character.is_ascii_digit())
+                {
+                    text.push(character);
+                }
+            }
+        }
+    }
+}
+
+/// Handle the in-game buttons that restart or leave the game
+fn press_game_buttons(
+    restart_buttons: Query<&Interaction, (With<RestartButton>, Changed<Interaction>)>,
+    quit_buttons: Query<&Interaction, (With<QuitButton>, Changed<Interaction>)>,
+    mut screen: ResMut<Screen>,
+) {
+    for interaction in restart_buttons.iter() {
+        if let Interaction::Clicked = interaction {
+            // Entering the game screen again starts a new game
+            screen.set_changed();
+        }
+    }
+
+    for interaction in quit_buttons.iter() {
+        if let Interaction::Clicked = interaction {
+            *screen = Screen::Menu;
+        }
+    }
+}
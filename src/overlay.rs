@@ -0,0 +1,245 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{Board, BoardIndex},
+    building::BuildingSlot,
+    chit::{Chit, ChitSlot},
+    knight::KnightSlot,
+    menu::in_game,
+    resource::{RESOURCES, RESOURCE_COUNT},
+    robber::RobberSlot,
+    tile::{Tile, TileEffect},
+    ui::OverlayButton,
+};
+
+pub struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Overlay>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(toggle_overlay)
+                .with_system(update_overlay.after(toggle_overlay)),
+        );
+    }
+}
+
+/// Whether the production odds overlay is showing
+#[derive(Default, Deref, DerefMut)]
+pub struct Overlay(bool);
+
+/// Dots under a chit, one for each way to roll its number
+#[derive(Component)]
+pub struct PipLabel;
+
+/// Tints a corner by how much it would produce, compared to the best open corner
+#[derive(Component)]
+pub struct HeatSpot;
+
+/// The cards a settlement on a corner would get per roll, in total, and by resource
+#[derive(Component)]
+pub struct OddsLabel;
+
+pub const OVERLAY_FONT_SIZE: f32 = 16.;
+/// The odds by resource are smaller than the total, so the corners don't run into each other
+const BREAKDOWN_FONT_SIZE: f32 = 10.;
+/// How far the pips are under the center of their tile, so they're under the chit
+pub const PIP_OFFSET: f32 = 24.;
+pub const HEAT_SIZE: f32 = 40.;
+/// The tint of the best open corner. Worse corners fade out from here.
+const HEAT_COLOR: Color = Color::rgba(1., 0.3, 0., 0.8);
+/// The 6 and 8 come up the most, so their pips are red, like their numbers
+const HOT_PIP_COLOR: Color = Color::RED;
+/// Every roll of two dice
+const ROLLS: f32 = 36.;
+
+/// How many of the 36 rolls of two dice add up to the chit's number
+pub fn pips(chit: Chit) -> u8 {
+    6 - (7 - *chit as i8).unsigned_abs()
+}
+
+/// The cards that a settlement would get per roll, on average
+#[derive(Clone, Copy, Default)]
+pub struct Odds {
+    /// By resource
+    pub resources: [f32; RESOURCE_COUNT],
+    /// Gold can be traded for any resource
+    pub gold: f32,
+}
+
+impl Odds {
+    /// The odds for a settlement on the corner. Tiles that have the robber don't count, since they don't produce.
+    pub fn new(
+        index: usize,
+        board: &Board,
+        tiles: &Query<&Tile>,
+        chits: &Query<&ChitSlot>,
+        robbers: &Query<&RobberSlot>,
+    ) -> Self {
+        let mut odds = Self::default();
+
+        for tile in &board.layout.building_tiles[index] {
+            if **robbers.get(board.robbers[*tile]).unwrap() {
+                continue;
+            }
+
+            if let Some(chit) = **chits.get(board.chits[*tile]).unwrap() {
+                let cards = pips(chit) as f32 / ROLLS;
+
+                match tiles.get(board.tiles[*tile]).unwrap().effect() {
+                    Some(TileEffect::Produce(resource)) => {
+                        odds.resources[resource as usize] += cards
+                    }
+                    Some(TileEffect::ChooseResource) => odds.gold += cards,
+                    Some(TileEffect::Erupt) | None => (),
+                }
+            }
+        }
+
+        odds
+    }
+
+    pub fn total(&self) -> f32 {
+        self.resources.iter().sum::<f32>() + self.gold
+    }
+
+    /// The name of everything the settlement would get, and how much of it per roll
+    pub fn by_name(self) -> impl Iterator<Item = (&'static str, f32)> {
+        RESOURCES
+            .into_iter()
+            .map(move |resource| (resource.name(), self.resources[resource as usize]))
+            .chain([("Gold", self.gold)])
+            .filter(|(_, cards)| *cards > 0.)
+    }
+}
+
+/// Whether a settlement could go on the corner at some point: it's on land, and there are no pieces on or next to it
+fn open(
+    index: usize,
+    board: &Board,
+    buildings: &Query<&BuildingSlot>,
+    knights: &Query<&KnightSlot>,
+) -> bool {
+    board.layout.building_islands[index].is_some()
+        && buildings.get(board.buildings[index]).unwrap().is_none()
+        && knights.get(board.knights[index]).unwrap().is_none()
+        && board.layout.building_buildings[index]
+            .iter()
+            .all(|building| buildings.get(board.buildings[*building]).unwrap().is_none())
+}
+
+/// Show or hide the overlay with the Odds button or O
+fn toggle_overlay(
+    buttons: Query<&Interaction, (With<OverlayButton>, Changed<Interaction>)>,
+    keys: Res<Input<KeyCode>>,
+    mut overlay: ResMut<Overlay>,
+) {
+    if keys.just_pressed(KeyCode::O)
+        || buttons
+            .iter()
+            .any(|interaction| matches!(interaction, Interaction::Clicked))
+    {
+        **overlay = !**overlay;
+    }
+}
+
+/// Fill in the overlay when it's shown, and when buildings, knights, or the robber change,
+/// including when a build is undone.
+fn update_overlay(
+    mut pip_labels: Query<(&mut Text, &mut Visibility, &BoardIndex), With<PipLabel>>,
+    mut odds_labels: Query<
+        (&mut Text, &mut Visibility, &BoardIndex),
+        (With<OddsLabel>, Without<PipLabel>),
+    >,
+    mut heat_spots: Query<
+        (&mut Sprite, &mut Visibility, &BoardIndex),
+        (With<HeatSpot>, Without<PipLabel>, Without<OddsLabel>),
+    >,
+    changed_buildings: Query<(), Changed<BuildingSlot>>,
+    changed_knights: Query<(), Changed<KnightSlot>>,
+    changed_robbers: Query<(), Changed<RobberSlot>>,
+    tiles: Query<&Tile>,
+    chits: Query<&ChitSlot>,
+    robbers: Query<&RobberSlot>,
+    buildings: Query<&BuildingSlot>,
+    knights: Query<&KnightSlot>,
+    board: Res<Board>,
+    overlay: Res<Overlay>,
+) {
+    if !overlay.is_changed()
+        && changed_buildings.is_empty()
+        && changed_knights.is_empty()
+        && changed_robbers.is_empty()
+    {
+        return;
+    }
+
+    for (mut text, mut visibility, index) in pip_labels.iter_mut() {
+        let chit = **chits.get(board.chits[**index]).unwrap();
+        visibility.is_visible = **overlay && chit.is_some();
+
+        if let Some(chit) = chit {
+            let pips = pips(chit);
+            text.sections[0].value = "•".repeat(pips as usize);
+            text.sections[0].style.color = if pips == 5 {
+                HOT_PIP_COLOR
+            } else {
+                Color::WHITE
+            };
+        }
+    }
+
+    // Only the open corners are rated
+    let odds: Vec<_> = (0..board.layout.building_positions.len())
+        .map(|index| {
+            (**overlay && open(index, &board, &buildings, &knights))
+                .then(|| Odds::new(index, &board, &tiles, &chits, &robbers))
+                .filter(|odds| odds.total() > 0.)
+        })
+        .collect();
+    let best = odds
+        .iter()
+        .flatten()
+        .map(|odds| odds.total())
+        .fold(0., f32::max);
+
+    for (mut text, mut visibility, index) in odds_labels.iter_mut() {
+        visibility.is_visible = odds[**index].is_some();
+
+        if let Some(odds) = odds[**index] {
+            let style = text.sections[0].style.clone();
+            let breakdown: String = odds
+                .by_name()
+                .map(|(name, cards)| format!("\n{cards:.2} {name}"))
+                .collect();
+
+            // The total on top, then each resource under it
+            text.sections = vec![
+                TextSection {
+                    value: format!("{:.2}", odds.total()),
+                    style: style.clone(),
+                },
+                TextSection {
+                    value: breakdown,
+                    style: TextStyle {
+                        font_size: BREAKDOWN_FONT_SIZE,
+                        ..style
+                    },
+                },
+            ];
+        }
+    }
+
+    for (mut sprite, mut visibility, index) in heat_spots.iter_mut() {
+        visibility.is_visible = odds[**index].is_some();
+
+        if let Some(odds) = odds[**index] {
+            // Squared, so that the best spots stand out
+            let heat = (odds.total() / best).powi(2);
+            let mut color = HEAT_COLOR;
+            color.set_a(HEAT_COLOR.a() * heat);
+            sprite.color = color;
+        }
+    }
+}
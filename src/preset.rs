@@ -0,0 +1,233 @@
+use std::fs;
+
+use bevy::asset::FileAssetIo;
+use serde::Deserialize;
+
+use crate::{chit::Chit, harbor::Harbor, hex::Hex, tile::Tile};
+
+/// Where board presets are kept, relative to the assets folder
+const BOARDS_PATH: &str = "boards";
+/// The board that's picked when the game starts
+pub const DEFAULT_BOARD: &str = "random";
+
+/// What a hex on the board is, before the tiles are dealt
+#[derive(Clone, Copy, Deserialize, PartialEq)]
+pub enum Terrain {
+    /// Gets a tile from the tile bag
+    Land,
+    Desert,
+    Sea,
+}
+
+/// A harbor on the edge between a land hex and a sea hex
+#[derive(Clone, Copy, Deserialize)]
+pub struct HarborSpot {
+    pub land: Hex,
+    pub sea: Hex,
+}
+
+/// A board, as defined in a RON file in `assets/boards/`.
+/// Bags are shuffled when the board is generated. If a bag is missing, its items are picked at random.
+#[derive(Deserialize)]
+pub struct BoardPreset {
+    pub name: String,
+    /// How many players fit around the board
+    #[serde(default = "default_max_players")]
+    pub max_players: usize,
+    pub hexes: Vec<(Hex, Terrain)>,
+    /// One tile for each `Land` hex
+    #[serde(default)]
+    pub tiles: Option<Vec<Tile>>,
+    /// One chit for each tile that does something when its number is rolled. Needs a tile bag, to know how many that is.
+    #[serde(default)]
+    pub chits: Option<Vec<Chit>>,
+    /// If missing, every coastal edge might get a harbor
+    #[serde(default)]
+    pub harbors: Option<Vec<HarborSpot>>,
+    /// One harbor for each harbor spot
+    #[serde(default)]
+    pub harbor_bag: Option<Vec<Harbor>>,
+    /// Victory points for each island a player settles, other than the ones they started on
+    #[serde(default)]
+    pub island_bonus: u8,
+    /// If missing, the robber starts on the deserts
+    #[serde(default)]
+    pub robber: Option<Hex>,
+}
+
+fn default_max_players() -> usize {
+    4
+}
+
+impl BoardPreset {
+    /// The harbor spots as land and sea hex pairs, if the board picks them
+    pub fn harbor_edges(&self) -> Option<Vec<[Hex; 2]>> {
+        self.harbors.as_ref().map(|harbors| {
+            harbors
+                .iter()
+                .map(|harbor| [harbor.land, harbor.sea])
+                .collect()
+        })
+    }
+
+    /// Whether there's sea inside the board, for ships to cross.
+    /// Every board has a frame of sea around it, but that only runs along the coast, so it doesn't count.
+    pub fn has_open_sea(&self) -> bool {
+        self.hexes.iter().any(|(hex, terrain)| {
+            *terrain == Terrain::Sea
+                && self
+                    .hexes
+                    .iter()
+                    .filter(|(other, _)| hex.is_neighbor(*other))
+                    .count()
+                    == 6
+        })
+    }
+
+    fn terrain(&self, hex: Hex) -> Option<Terrain> {
+        self.hexes
+            .iter()
+            .find(|(other, _)| *other == hex)
+            .map(|(_, terrain)| *terrain)
+    }
+
+    /// Checks that the bags fit the board, so a broken file is caught before anyone plays on it
+    fn validate(&self) -> Result<(), String> {
+        let land_count = self
+            .hexes
+            .iter()
+            .filter(|(_, terrain)| *terrain == Terrain::Land)
+            .count();
+
+        if let Some(tiles) = &self.tiles {
+            if tiles.len() != land_count {
+                return Err(format!(
+                    "{} tiles in the bag for {land_count} land hexes",
+                    tiles.len()
+                ));
+            }
+        }
+
+        if let Some(chits) = &self.chits {
+            let tiles = self.tiles.as_ref().ok_or("a chit bag needs a tile bag")?;
+            let chit_count = tiles.iter().filter(|tile| tile.effect().is_some()).count();
+
+            if chits.len() != chit_count {
+                return Err(format!(
+                    "{} chits in the bag for {chit_count} tiles with numbers",
+                    chits.len()
+                ));
+            }
+
+            if let Some(value) = chits
+                .iter()
+                .map(|chit| **chit)
+                .find(|value| !(2..=12).contains(value) || *value == 7)
+            {
+                return Err(format!("invalid chit with value: {value}"));
+            }
+        }
+
+        if let Some(harbors) = &self.harbors {
+            for harbor in harbors {
+                if !matches!(
+                    self.terrain(harbor.land),
+                    Some(Terrain::Land | Terrain::Desert)
+                ) || self.terrain(harbor.sea) != Some(Terrain::Sea)
+                    || !harbor.land.is_neighbor(harbor.sea)
+                {
+                    return Err(format!(
+                        "harbor between {:?} and {:?} isn't between neighboring land and sea hexes",
+                        harbor.land, harbor.sea
+                    ));
+                }
+            }
+        }
+
+        if let Some(harbor_bag) = &self.harbor_bag {
+            let harbors = self
+                .harbors
+                .as_ref()
+                .ok_or("a harbor bag needs harbor spots")?;
+
+            if harbor_bag.len() != harbors.len() {
+                return Err(format!(
+                    "{} harbors in the bag for {} harbor spots",
+                    harbor_bag.len(),
+                    harbors.len()
+                ));
+            }
+        }
+
+        if let Some(robber) = self.robber {
+            if !matches!(self.terrain(robber), Some(Terrain::Land | Terrain::Desert)) {
+                return Err(format!("robber starts off the board at {robber:?}"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Every board in `assets/boards/`, by file name, without the extension
+pub struct BoardPresets(Vec<(String, BoardPreset)>);
+
+impl Default for BoardPresets {
+    fn default() -> Self {
+        let path = FileAssetIo::get_root_path()
+            .join("assets")
+            .join(BOARDS_PATH);
+        let mut presets: Vec<_> = fs::read_dir(&path)
+            .unwrap_or_else(|error| panic!("couldn't read boards from {path:?}: {error}"))
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension() == Some("ron".as_ref()))
+            .map(|path| {
+                let file = fs::read_to_string(&path)
+                    .unwrap_or_else(|error| panic!("couldn't read board {path:?}: {error}"));
+                let preset: BoardPreset = ron::from_str(&file)
+                    .unwrap_or_else(|error| panic!("couldn't parse board {path:?}: {error}"));
+
+                if let Err(error) = preset.validate() {
+                    panic!("invalid board {path:?}: {error}");
+                }
+
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                (name, preset)
+            })
+            .collect();
+
+        if presets.is_empty() {
+            panic!("no boards in {path:?}");
+        }
+
+        presets.sort_by(|(left, _), (right, _)| left.cmp(right));
+        Self(presets)
+    }
+}
+
+impl BoardPresets {
+    /// Gets the board with the given file name, or the first board if there's no such board
+    pub fn get(&self, name: &str) -> &BoardPreset {
+        &self
+            .0
+            .iter()
+            .find(|(other, _)| other == name)
+            .unwrap_or(&self.0[0])
+            .1
+    }
+
+    /// Gets the file name of the next board after the given one that fits the players, wrapping around.
+    /// If no other board fits, gets the given one.
+    pub fn after<'a>(&'a self, name: &'a str, player_count: usize) -> &'a str {
+        let start = self
+            .0
+            .iter()
+            .position(|(other, _)| other == name)
+            .map_or(0, |i| i + 1);
+
+        (0..self.0.len())
+            .map(|offset| &self.0[(start + offset) % self.0.len()])
+            .find(|(_, preset)| preset.max_players >= player_count)
+            .map_or(name, |(other, _)| other)
+    }
+}
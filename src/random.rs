@@ -10,10 +10,11 @@ pub trait Shuffle: Copy + Sized + 'static {
     fn weight(self) -> f32;
 
     /// Get a list of weighted random variants
-    fn shuffle<const COUNT: usize>() -> [Self; COUNT] {
-        let mut rng = thread_rng();
+    fn shuffle(count: usize, rng: &mut impl Rng) -> Vec<Self> {
         let weights =
             WeightedIndex::new(Self::pool().iter().map(|variant| variant.weight())).unwrap();
-        [(); COUNT].map(|_| Self::pool()[weights.sample(&mut rng)])
+        (0..count)
+            .map(|_| Self::pool()[weights.sample(rng)])
+            .collect()
     }
 }
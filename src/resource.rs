@@ -1,13 +1,28 @@
 use bevy::prelude::*;
 use rand::{thread_rng, Rng};
+use serde::Deserialize;
 
 use crate::{
-    board::{Board, BoardIndex, BUILDING_TILE_ADJACENCY},
-    building::BuildingSlot,
+    barbarian::Barbarians,
+    board::{Board, BoardIndex},
+    building::{Building, BuildingSlot, BuildingType},
     chit::ChitSlot,
-    tile::Tile,
-    turn::{Turn, PLAYER_COUNT},
-    ui::{Die1, Die2, HandUi},
+    color::COLOR_COUNT,
+    commodity::{Commodities, Commodity, COMMODITIES, COMMODITY_COUNT},
+    discard::Discards,
+    event_deck::{Event, EventDeck},
+    gold::GoldChoices,
+    hot_seat::{HotSeat, Viewer},
+    island::HomeIslands,
+    log::{GameLog, LogEvent},
+    menu::{in_game, GameSetup, Ruleset},
+    preset::BoardPresets,
+    robber::{visible_points, RobberSlot},
+    roll::Roll,
+    rules::Rules,
+    tile::{Tile, TileEffect},
+    turn::{Players, Turn},
+    ui::HandUi,
 };
 
 pub struct ResourcePlugin;
@@ -15,12 +30,17 @@ pub struct ResourcePlugin;
 impl Plugin for ResourcePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<Hands>()
-            .add_system(update_hand_ui)
-            .add_system(produce_resources);
+            .init_resource::<Commodities>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(in_game)
+                    .with_system(update_hand_ui)
+                    .with_system(produce_resources),
+            );
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize, Eq, Hash, PartialEq)]
 pub enum Resource {
     Brick,
     Wool,
@@ -30,7 +50,7 @@ pub enum Resource {
 }
 
 impl Resource {
-    fn image(self) -> &'static str {
+    pub fn image(self) -> &'static str {
         match self {
             Self::Brick => "brick.png",
             Self::Wool => "wool.png",
@@ -39,15 +59,25 @@ impl Resource {
             Self::Lumber => "lumber.png",
         }
     }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Brick => "Brick",
+            Self::Wool => "Wool",
+            Self::Ore => "Ore",
+            Self::Grain => "Grain",
+            Self::Lumber => "Lumber",
+        }
+    }
 }
 
-const RESOURCE_COUNT: usize = 5;
+pub const RESOURCE_COUNT: usize = 5;
 
 /// Indexed by a `PlayerColor as usize`, and then a `Resource as usize`
 #[derive(Default, Deref, DerefMut)]
-pub struct Hands([[u8; RESOURCE_COUNT]; PLAYER_COUNT]);
+pub struct Hands([[u8; RESOURCE_COUNT]; COLOR_COUNT]);
 
-const RESOURCES: [Resource; RESOURCE_COUNT] = [
+pub const RESOURCES: [Resource; RESOURCE_COUNT] = [
     Resource::Brick,
     Resource::Wool,
     Resource::Ore,
@@ -55,90 +85,330 @@ const RESOURCES: [Resource; RESOURCE_COUNT] = [
     Resource::Lumber,
 ];
 const RESOURCE_SIZE: Val = Val::Px(32.);
+const HAND_FONT_SIZE: f32 = 30.;
+const BADGE_FONT_SIZE: f32 = 16.;
+/// Tints the cards that a player has none of
+const EMPTY_CARD_COLOR: Color = Color::rgb(0.4, 0.4, 0.4);
+
+/// What a hand in the UI is showing, so it's only rebuilt when that changes
+#[derive(Component, PartialEq)]
+struct ShownHand {
+    /// Whether only the number of cards is shown
+    hidden: bool,
+    resources: [u8; RESOURCE_COUNT],
+    commodities: [u8; COMMODITY_COUNT],
+    points: u8,
+}
 
-/// When a player's hand changes, update the hand in the UI
+/// Show each player's cards and victory points in the UI, and rebuild a player's hand only when it changes.
+/// Each kind of card gets one icon, with how many the player has on it.
+/// Commodities are shown after the resources, in Cities & Knights.
 fn update_hand_ui(
     mut commands: Commands,
-    hand_uis: Query<(Entity, &HandUi)>,
+    hand_uis: Query<(Entity, &HandUi, Option<&ShownHand>)>,
+    changed_buildings: Query<(), Changed<BuildingSlot>>,
+    buildings: Query<(&BuildingSlot, &BoardIndex)>,
+    board: Res<Board>,
     hands: Res<Hands>,
+    commodities: Res<Commodities>,
+    barbarians: Res<Barbarians>,
+    home_islands: Res<HomeIslands>,
+    hot_seat: Res<HotSeat>,
+    viewer: Res<Viewer>,
+    setup: Res<GameSetup>,
+    presets: Res<BoardPresets>,
     assets: Res<AssetServer>,
 ) {
-    if hands.is_changed() {
-        for (entity, hand) in hand_uis.iter() {
-            let mut hand_commands = commands.entity(entity);
-            hand_commands.despawn_descendants();
-
-            hand_commands.with_children(|parent| {
-                for (resource, count) in hands[hand.color as usize].into_iter().enumerate() {
-                    for _ in 0..count {
-                        parent.spawn_bundle(ImageBundle {
+    let new_hand_ui = hand_uis.iter().any(|(_, _, shown)| shown.is_none());
+
+    if !new_hand_ui
+        && changed_buildings.is_empty()
+        && !hands.is_changed()
+        && !commodities.is_changed()
+        && !barbarians.is_changed()
+        && !home_islands.is_changed()
+        && !hot_seat.is_changed()
+        && !viewer.is_changed()
+    {
+        return;
+    }
+
+    let points = visible_points(
+        &buildings,
+        &board,
+        &barbarians,
+        &home_islands,
+        presets.get(&setup.board).island_bonus,
+    );
+    let font = assets.load("FiraSans-Bold.ttf");
+
+    for (entity, hand, shown) in hand_uis.iter() {
+        let color = hand.color as usize;
+        let hand_shown = ShownHand {
+            // In hot-seat mode, the other players only get to see how many cards you have
+            hidden: **hot_seat && **viewer != Some(hand.color),
+            resources: hands[color],
+            commodities: commodities[color],
+            points: points[color],
+        };
+
+        if shown == Some(&hand_shown) {
+            continue;
+        }
+
+        let mut hand_commands = commands.entity(entity);
+        hand_commands.despawn_descendants();
+
+        hand_commands.with_children(|parent| {
+            if hand_shown.hidden {
+                let count: u8 = hand_shown.resources.iter().sum::<u8>()
+                    + hand_shown.commodities.iter().sum::<u8>();
+
+                parent.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        format!("{count} cards "),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: HAND_FONT_SIZE,
+                            color: default(),
+                        },
+                        default(),
+                    ),
+                    ..default()
+                });
+            } else {
+                let resources = RESOURCES
+                    .into_iter()
+                    .map(|resource| (resource.image(), hand_shown.resources[resource as usize]));
+                let commodities = COMMODITIES
+                    .into_iter()
+                    .map(|commodity| {
+                        (
+                            commodity.image(),
+                            hand_shown.commodities[commodity as usize],
+                        )
+                    })
+                    .filter(|_| setup.ruleset == Ruleset::CitiesAndKnights);
+
+                for (image, count) in resources.chain(commodities) {
+                    parent
+                        .spawn_bundle(ImageBundle {
                             style: Style {
                                 size: Size::new(RESOURCE_SIZE, RESOURCE_SIZE),
                                 ..default()
                             },
-                            image: assets.load(RESOURCES[resource].image()).into(),
+                            color: if count == 0 {
+                                EMPTY_CARD_COLOR
+                            } else {
+                                Color::WHITE
+                            }
+                            .into(),
+                            image: assets.load(image).into(),
                             ..default()
+                        })
+                        .with_children(|parent| {
+                            // Count badge
+                            parent.spawn_bundle(TextBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    position: Rect {
+                                        right: Val::Px(0.),
+                                        bottom: Val::Px(0.),
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                                text: Text::with_section(
+                                    count.to_string(),
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: BADGE_FONT_SIZE,
+                                        color: default(),
+                                    },
+                                    default(),
+                                ),
+                                ..default()
+                            });
                         });
-                    }
                 }
+            }
+
+            // Victory points
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    format!(" {} VP", hand_shown.points),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: HAND_FONT_SIZE,
+                        color: default(),
+                    },
+                    default(),
+                ),
+                ..default()
             });
-        }
+        });
+
+        hand_commands.insert(hand_shown);
     }
 }
 
-/// Roll the dice and give the players their resources
-fn produce_resources(
-    buildings: Query<(&BuildingSlot, &BoardIndex)>,
+/// Roll the dice, or draw from the event deck, and give the players their resources
+pub fn produce_resources(
+    mut buildings: Query<(&mut BuildingSlot, &BoardIndex)>,
     tiles: Query<&Tile>,
     chits: Query<&ChitSlot>,
-    mut die_1s: Query<&mut UiImage, (With<Die1>, Without<Die2>)>,
-    mut die_2s: Query<&mut UiImage, (With<Die2>, Without<Die1>)>,
+    robbers: Query<&RobberSlot>,
     board: Res<Board>,
-    assets: Res<AssetServer>,
+    players: Res<Players>,
+    setup: Res<GameSetup>,
+    rules: Res<Rules>,
     mut hands: ResMut<Hands>,
+    mut commodities: ResMut<Commodities>,
+    mut gold_choices: ResMut<GoldChoices>,
+    mut discards: ResMut<Discards>,
+    mut event_deck: ResMut<EventDeck>,
+    mut roll: ResMut<Roll>,
+    mut log: ResMut<GameLog>,
     mut turn: ResMut<Turn>,
 ) {
     if turn.is_changed() {
-        if let Turn::Production { .. } = *turn {
-            let mut rng = thread_rng();
-
-            let roll_1 = rng.gen_range(1..=6);
-            let roll_2 = rng.gen_range(1..=6);
-
-            for (roll, mut image) in
-                [(roll_1, die_1s.single_mut()), (roll_2, die_2s.single_mut())].into_iter()
-            {
-                *image = assets
-                    .load(match roll {
-                        1 => "die_1.png",
-                        2 => "die_2.png",
-                        3 => "die_3.png",
-                        4 => "die_4.png",
-                        5 => "die_5.png",
-                        6 => "die_6.png",
-                        _ => panic!("Invalid die roll"),
-                    })
-                    .into();
-            }
+        if let Turn::Production { player } = *turn {
+            let (roll_1, roll_2, event) = if setup.event_deck {
+                let card = event_deck.draw();
+                (card.dice[0], card.dice[1], card.event)
+            } else {
+                let mut rng = thread_rng();
+                (rng.gen_range(1..=6), rng.gen_range(1..=6), None)
+            };
 
             let total = roll_1 + roll_2;
+            log.push(
+                players[player],
+                LogEvent::Rolled {
+                    dice: [roll_1, roll_2],
+                },
+            );
+            // What everyone had, to log what they got
+            let (old_hands, old_commodities) = (**hands, **commodities);
+
+            if let Some(Event::PlentifulYear) = event {
+                for color in players.iter() {
+                    gold_choices[*color as usize] += 1;
+                }
+            }
+
+            // Nothing is produced in an earthquake
+            let earthquake = matches!(event, Some(Event::Earthquake));
+            // The tiles whose number came up, split by whether they get to produce
+            let (producing, blocked): (Vec<_>, Vec<_>) = (0..board.tiles.len())
+                .filter(|tile| {
+                    matches!(**chits.get(board.chits[*tile]).unwrap(), Some(chit) if *chit == total)
+                })
+                .partition(|tile| !earthquake && !**robbers.get(board.robbers[*tile]).unwrap());
+            // Every card handed out, for the players to watch go by
+            let mut cards = Vec::new();
 
-            for (building, index) in buildings.iter() {
-                if let Some(building) = **building {
-                    let hand = &mut hands[building.color as usize];
+            for (mut slot, index) in buildings.iter_mut().filter(|_| !earthquake) {
+                if let Some(building) = **slot {
+                    let color = building.color as usize;
+                    let production = building.building_type.production();
+                    // In Cities & Knights, cities make a commodity in place of their second resource,
+                    // if the tile has one
+                    let makes_commodities = setup.ruleset == Ruleset::CitiesAndKnights
+                        && matches!(building.building_type, BuildingType::City);
+                    let mut erupted = false;
 
-                    for tile in BUILDING_TILE_ADJACENCY[**index] {
-                        if let Some(chit) = **chits.get(board.chits[*tile]).unwrap() {
-                            if total == *chit {
-                                hand[tiles.get(board.tiles[*tile]).unwrap().resource().unwrap()
-                                    as usize] += building.building_type.production();
+                    for tile in &board.layout.building_tiles[**index] {
+                        // The robber keeps its tile from producing
+                        if producing.contains(tile) {
+                            match tiles.get(board.tiles[*tile]).unwrap().effect() {
+                                Some(TileEffect::Produce(resource)) => {
+                                    match Commodity::from_resource(resource)
+                                        .filter(|_| makes_commodities)
+                                    {
+                                        Some(commodity) => {
+                                            hands[color][resource as usize] += 1;
+                                            commodities[color][commodity as usize] += 1;
+                                            cards.push((*tile, building.color, resource.image()));
+                                            cards.push((*tile, building.color, commodity.image()));
+                                        }
+                                        None => {
+                                            hands[color][resource as usize] += production;
+                                            cards.extend((0..production).map(|_| {
+                                                (*tile, building.color, resource.image())
+                                            }));
+                                        }
+                                    }
+                                }
+                                Some(TileEffect::ChooseResource) => {
+                                    gold_choices[color] += production
+                                }
+                                Some(TileEffect::Erupt) => erupted = true,
+                                None => (),
                             }
                         }
                     }
+
+                    // The building still produces from the other tiles as it burns
+                    if erupted {
+                        **slot = match building.building_type {
+                            BuildingType::Settlement => None,
+                            BuildingType::City => Some(Building {
+                                building_type: BuildingType::Settlement,
+                                ..building
+                            }),
+                        };
+                    }
+                }
+            }
+
+            for color in players.iter() {
+                let color = *color;
+                let mut resources = hands[color as usize];
+                for (count, old) in resources.iter_mut().zip(old_hands[color as usize]) {
+                    *count -= old;
+                }
+                let mut commodities = commodities[color as usize];
+                for (count, old) in commodities.iter_mut().zip(old_commodities[color as usize]) {
+                    *count -= old;
                 }
+
+                if resources
+                    .iter()
+                    .chain(commodities.iter())
+                    .any(|count| *count > 0)
+                {
+                    log.push(
+                        color,
+                        LogEvent::Received {
+                            resources,
+                            commodities,
+                        },
+                    );
+                }
+            }
+
+            *roll = Roll {
+                dice: Some([roll_1, roll_2]),
+                producing,
+                blocked,
+                cards,
+            };
+
+            // On a 7, anyone with too many cards discards, in turn order, before the robber moves
+            if total == 7 {
+                *discards = Discards::new(&hands, rules.discard_limit);
             }
 
-            *turn = turn.next();
+            // Anyone who hit gold, or had a plentiful year, gets to choose their resources, in turn order
+            *turn = match gold_choices.next_chooser(&players, player, None) {
+                Some(chooser) => Turn::ChooseResource { player, chooser },
+                None if total == 7 => match discards.next_discarder(&players, player, None) {
+                    Some(discarder) => Turn::Discard { player, discarder },
+                    None => Turn::MoveRobber { player },
+                },
+                None => turn.next(players.len()),
+            };
         }
     }
 }
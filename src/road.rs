@@ -1,24 +1,32 @@
+use std::f32::consts::TAU;
+
 use bevy::prelude::*;
 
 use crate::{
-    board::{
-        Board, BoardIndex, BUILDING_ROAD_ADJACENCY, ROAD_BUILDING_ADJACENCY, ROAD_ORIENTATIONS,
-        ROAD_ROAD_ADJACENCY,
-    },
+    board::{Board, BoardIndex},
     building::BuildingSlot,
     button::{Clicked, RoadButton},
     color::PlayerColor,
     image::UpdateImages,
-    resource::{Hands, Resource},
+    menu::in_game,
+    resource::Hands,
+    rules::Rules,
+    ship::ShipSlot,
     turn::{Players, Turn},
     ui::BuildRoadButton,
+    undo::{Action, Placement, UndoStack},
 };
 
 pub struct RoadPlugin;
 
 impl Plugin for RoadPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(show_road_buttons).add_system(build_road);
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(show_road_buttons)
+                .with_system(build_road),
+        );
     }
 }
 
@@ -33,6 +41,19 @@ pub enum RoadOrientation {
     Vert,
 }
 
+impl RoadOrientation {
+    /// A unit vector along the road, from left to right, or from the bottom up
+    pub fn direction(self) -> Vec2 {
+        let angle = match self {
+            Self::Inc => TAU / 12.,
+            Self::Dec => -TAU / 12.,
+            Self::Vert => TAU / 4.,
+        };
+
+        Vec2::new(angle.cos(), angle.sin())
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Road {
     pub color: PlayerColor,
@@ -42,8 +63,8 @@ pub struct Road {
 pub struct RoadSlot(pub Option<Road>);
 
 impl UpdateImages for RoadSlot {
-    fn image(self, index: usize) -> Option<&'static str> {
-        let orientation = ROAD_ORIENTATIONS[index];
+    fn image(self, board: &Board, index: usize) -> Option<&'static str> {
+        let orientation = board.layout.road_orientations[index];
         match *self {
             None => None,
             Some(Road {
@@ -74,10 +95,73 @@ impl UpdateImages for RoadSlot {
                 RoadOrientation::Dec => "white_dec_road.png",
                 RoadOrientation::Vert => "white_vert_road.png",
             }),
+            Some(Road {
+                color: PlayerColor::Green,
+            }) => Some(match orientation {
+                RoadOrientation::Inc => "green_inc_road.png",
+                RoadOrientation::Dec => "green_dec_road.png",
+                RoadOrientation::Vert => "green_vert_road.png",
+            }),
+            Some(Road {
+                color: PlayerColor::Brown,
+            }) => Some(match orientation {
+                RoadOrientation::Inc => "brown_inc_road.png",
+                RoadOrientation::Dec => "brown_dec_road.png",
+                RoadOrientation::Vert => "brown_vert_road.png",
+            }),
         }
     }
 }
 
+/// Whether the player may build a road here
+pub fn can_build_road(
+    index: usize,
+    color: PlayerColor,
+    setup: bool,
+    board: &Board,
+    buildings: &Query<&BuildingSlot>,
+    roads: &Query<&RoadSlot>,
+    ships: &Query<&ShipSlot>,
+) -> bool {
+    // Roads go on land, and can't share an edge with a ship
+    board.layout.road_land[index]
+        && ships.get(board.ships[index]).unwrap().is_none()
+        && if setup {
+            // We're in a setup phase
+            // The player must build a road here if it's next to the building of their color that isn't next to any roads
+            board.layout.road_buildings[index]
+                .into_iter()
+                .any(|building| {
+                    buildings
+                        .get(board.buildings[building])
+                        .unwrap()
+                        .map(|building| building.color == color)
+                        .unwrap_or(false)
+                        && board.layout.building_roads[building]
+                            .iter()
+                            .all(|road| roads.get(board.roads[*road]).unwrap().is_none())
+                })
+        } else {
+            // We're in a build phase
+            // The player may build a road here if one of its ends has their building,
+            // or has another of their roads and no other player's building
+            roads.get(board.roads[index]).unwrap().is_none()
+                && board.layout.road_buildings[index]
+                    .into_iter()
+                    .any(
+                        |building| match **buildings.get(board.buildings[building]).unwrap() {
+                            Some(building) => building.color == color,
+                            None => board.layout.building_roads[building].iter().any(|road| {
+                                roads
+                                    .get(board.roads[*road])
+                                    .unwrap()
+                                    .map_or(false, |road| color == road.color)
+                            }),
+                        },
+                    )
+        }
+}
+
 /// Show the buttons that appear when building roads
 pub fn show_road_buttons(
     mut buttons: Query<(&mut Visibility, &BoardIndex), With<RoadButton>>,
@@ -85,74 +169,54 @@ pub fn show_road_buttons(
     build_buttons: Query<&Interaction, (With<BuildRoadButton>, Changed<Interaction>)>,
     buildings: Query<&BuildingSlot>,
     roads: Query<&RoadSlot>,
+    ships: Query<&ShipSlot>,
     board: Res<Board>,
     players: Res<Players>,
+    hands: Res<Hands>,
+    rules: Res<Rules>,
     mut turn: ResMut<Turn>,
-    mut hands: ResMut<Hands>,
 ) {
-    if let Some((player, setup)) = match *turn {
+    if let Some((player, special, setup)) = match *turn {
         // We're in a road-building phase of a setup round
         // Show the buttons if we've just entered the phase
         Turn::Setup {
             player, road: true, ..
-        } => turn.is_changed().then(|| (player, true)),
+        } => turn.is_changed().then(|| (player, None, true)),
         // We're in a build phase
-        // Show the buttons if the player pressed the button to build, and has enough resources
-        Turn::Build { player } => build_buttons.get_single().ok().and_then(|interaction| {
-            if let Interaction::Clicked = interaction {
-                let hand = hands[players[player] as usize];
-
-                (hand[Resource::Brick as usize] >= 1 && hand[Resource::Lumber as usize] >= 1)
-                    .then(|| (player, false))
-            } else {
-                None
-            }
-        }),
+        // Show the buttons if the player pressed the button to build, has enough resources,
+        // and has a road left to build
+        Turn::Build { player, special } => {
+            build_buttons.get_single().ok().and_then(|interaction| {
+                if let Interaction::Clicked = interaction {
+                    let color = players[player];
+                    let road_count = roads
+                        .iter()
+                        .filter(|road| matches!(***road, Some(road) if road.color == color))
+                        .count();
+
+                    (rules.road_cost.can_afford(hands[color as usize])
+                        && road_count < rules.max_roads)
+                        .then(|| (player, special, false))
+                } else {
+                    None
+                }
+            })
+        }
         _ => None,
     } {
         let mut can_build = false;
         let color = players[player];
 
         for (mut visibility, index) in buttons.iter_mut() {
-            let visible = if setup {
-                // We're in a setup phase
-                // The player must build a road here if it's next to the building of their color that isn't next to any roads
-                ROAD_BUILDING_ADJACENCY[**index]
-                    .into_iter()
-                    .any(|building| {
-                        buildings
-                            .get(board.buildings[building])
-                            .unwrap()
-                            .map(|building| building.color == color)
-                            .unwrap_or(false)
-                            && BUILDING_ROAD_ADJACENCY[building]
-                                .iter()
-                                .all(|road| roads.get(board.roads[*road]).unwrap().is_none())
-                    })
-            } else {
-                // We're in a build phase
-                // The player may build a road here if it's adjacent to another road of the same color
-                roads.get(board.roads[**index]).unwrap().is_none()
-                    && ROAD_ROAD_ADJACENCY[**index].iter().any(|road| {
-                        roads
-                            .get(board.roads[*road])
-                            .unwrap()
-                            .map_or(false, |road| color == road.color)
-                    })
-            };
+            let visible = can_build_road(**index, color, setup, &board, &buildings, &roads, &ships);
 
             visibility.is_visible = visible;
             can_build |= visible;
         }
 
-        // If they aren't in setup, they should be charged for the build
+        // If they aren't in setup, they're charged once they pick a spot
         if can_build && !setup {
-            let hand = &mut hands[color as usize];
-
-            hand[Resource::Brick as usize] -= 1;
-            hand[Resource::Lumber as usize] -= 1;
-
-            *turn = Turn::BuildRoad { player };
+            *turn = Turn::BuildRoad { player, special };
         }
     }
 }
@@ -165,18 +229,35 @@ fn build_road(
     mut roads: Query<&mut RoadSlot>,
     board: Res<Board>,
     players: Res<Players>,
+    rules: Res<Rules>,
     mut turn: ResMut<Turn>,
+    mut hands: ResMut<Hands>,
+    mut undo_stack: ResMut<UndoStack>,
 ) {
     // We're in a road-building phase of a setup round
     if let Turn::Setup {
         player, road: true, ..
     }
     // We're building a road because the player pressed the Build road button
-    | Turn::BuildRoad { player } = *turn
+    | Turn::BuildRoad { player, .. } = *turn
     {
         for (entity, index) in clicked_buttons.iter_mut() {
             commands.entity(entity).remove::<Clicked>();
 
+            // Roads are free in the setup phase, and can't be taken back
+            if let Turn::BuildRoad { .. } = *turn {
+                let color = players[player];
+                let hand = &mut hands[color as usize];
+
+                undo_stack.push(Action {
+                    color,
+                    hand: *hand,
+                    placement: Some(Placement::Road { index: **index }),
+                });
+
+                rules.road_cost.pay(hand);
+            }
+
             **roads.get_mut(board.roads[**index]).unwrap() = Some(Road {
                 color: players[player],
             });
@@ -185,7 +266,7 @@ fn build_road(
                 visibility.is_visible = false;
             }
 
-            *turn = turn.next();
+            *turn = turn.next(players.len());
         }
     }
 }
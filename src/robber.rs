@@ -1,13 +1,308 @@
 use bevy::prelude::*;
+use rand::{prelude::SliceRandom, thread_rng};
 
-use crate::image::UpdateImages;
+use crate::{
+    barbarian::Barbarians,
+    board::{Board, BoardIndex},
+    building::{BuildingSlot, BuildingType},
+    button::{BuildingButton, Clicked, TileButton},
+    color::{PlayerColor, COLOR_COUNT},
+    commodity::{Commodities, Commodity, COMMODITIES},
+    image::UpdateImages,
+    island::{island_points, HomeIslands},
+    log::{GameLog, LogEvent},
+    menu::{in_game, GameSetup},
+    preset::BoardPresets,
+    resource::{Hands, Resource, RESOURCES},
+    rules::Rules,
+    tile::Tile,
+    turn::{Players, Turn},
+};
+
+pub struct RobberPlugin;
+
+impl Plugin for RobberPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(show_robber_buttons)
+                .with_system(move_robber)
+                .with_system(show_steal_buttons)
+                .with_system(steal),
+        );
+    }
+}
 
 /// The `bool` represents whether there's a robber there
-#[derive(Clone, Component, Copy, Deref)]
+#[derive(Clone, Component, Copy, Deref, DerefMut)]
 pub struct RobberSlot(pub bool);
 
 impl UpdateImages for RobberSlot {
-    fn image(self, _: usize) -> Option<&'static str> {
+    fn image(self, _: &Board, _: usize) -> Option<&'static str> {
         self.then(|| "robber.png")
     }
 }
+
+/// A card in a player's hand, for stealing
+#[derive(Clone, Copy)]
+enum Card {
+    Resource(Resource),
+    Commodity(Commodity),
+}
+
+/// The victory points that everyone can see: buildings, settling islands, and defending the island.
+/// Indexed by a `PlayerColor as usize`.
+pub fn visible_points(
+    buildings: &Query<(&BuildingSlot, &BoardIndex)>,
+    board: &Board,
+    barbarians: &Barbarians,
+    home_islands: &HomeIslands,
+    island_bonus: u8,
+) -> [u8; COLOR_COUNT] {
+    let mut points = island_points(buildings, board, home_islands, island_bonus);
+
+    for (building, _) in buildings.iter() {
+        if let Some(building) = **building {
+            points[building.color as usize] += match building.building_type {
+                BuildingType::Settlement => 1,
+                BuildingType::City => 2,
+            };
+        }
+    }
+
+    for (points, defender_points) in points.iter_mut().zip(barbarians.defender_points) {
+        *points += defender_points;
+    }
+
+    points
+}
+
+/// Under the friendly robber rule, players with few victory points can't be robbed
+fn protected(
+    color: PlayerColor,
+    points: &[u8; COLOR_COUNT],
+    setup: &GameSetup,
+    rules: &Rules,
+) -> bool {
+    setup.friendly_robber && points[color as usize] <= rules.friendly_robber_points
+}
+
+/// The other players with a building on the tile, and the corner of each building
+fn neighbors(
+    tile: usize,
+    color: PlayerColor,
+    board: &Board,
+    buildings: &Query<&BuildingSlot>,
+) -> Vec<(usize, PlayerColor)> {
+    board
+        .layout
+        .building_tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, tiles)| tiles.contains(&tile))
+        .filter_map(|(index, _)| {
+            buildings
+                .get(board.buildings[index])
+                .unwrap()
+                .map(|building| (index, building.color))
+        })
+        .filter(|(_, other)| *other != color)
+        .collect()
+}
+
+/// Show a button on every tile the robber can move to, when the player rolls a 7.
+/// It has to move, and can't go on the sea, or next to only protected players.
+fn show_robber_buttons(
+    mut buttons: Query<(&mut Visibility, &BoardIndex), With<TileButton>>,
+    tiles: Query<&Tile>,
+    robbers: Query<&RobberSlot>,
+    buildings: Query<&BuildingSlot>,
+    indexed_buildings: Query<(&BuildingSlot, &BoardIndex)>,
+    board: Res<Board>,
+    players: Res<Players>,
+    barbarians: Res<Barbarians>,
+    home_islands: Res<HomeIslands>,
+    setup: Res<GameSetup>,
+    presets: Res<BoardPresets>,
+    rules: Res<Rules>,
+    mut turn: ResMut<Turn>,
+) {
+    if !turn.is_changed() {
+        return;
+    }
+
+    if let Turn::MoveRobber { player } = *turn {
+        let color = players[player];
+        let points = visible_points(
+            &indexed_buildings,
+            &board,
+            &barbarians,
+            &home_islands,
+            presets.get(&setup.board).island_bonus,
+        );
+        let mut can_move = false;
+
+        for (mut visibility, index) in buttons.iter_mut() {
+            let neighbors = neighbors(**index, color, &board, &buildings);
+            let visible = !matches!(tiles.get(board.tiles[**index]).unwrap(), Tile::Sea)
+                && !**robbers.get(board.robbers[**index]).unwrap()
+                && (neighbors.is_empty()
+                    || neighbors
+                        .iter()
+                        .any(|(_, other)| !protected(*other, &points, &setup, &rules)));
+
+            visibility.is_visible = visible;
+            can_move |= visible;
+        }
+
+        // Only happens if every tile is off limits, in which case the robber stays put
+        if !can_move {
+            *turn = turn.next(players.len());
+        }
+    }
+}
+
+/// Move the robber to the tile that the player picked
+fn move_robber(
+    mut commands: Commands,
+    clicked_buttons: Query<(Entity, &BoardIndex), (With<TileButton>, With<Clicked>)>,
+    mut buttons: Query<&mut Visibility, With<TileButton>>,
+    mut robbers: Query<(&mut RobberSlot, &BoardIndex)>,
+    players: Res<Players>,
+    mut log: ResMut<GameLog>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::MoveRobber { player } = *turn {
+        for (entity, index) in clicked_buttons.iter() {
+            commands.entity(entity).remove::<Clicked>();
+
+            for (mut robber, other) in robbers.iter_mut() {
+                **robber = **other == **index;
+            }
+
+            for mut visibility in buttons.iter_mut() {
+                visibility.is_visible = false;
+            }
+
+            log.push(players[player], LogEvent::MovedRobber);
+            *turn = Turn::Steal {
+                player,
+                tile: **index,
+            };
+        }
+    }
+}
+
+/// Show a button on the buildings of the players next to the robber that have something to steal
+fn show_steal_buttons(
+    mut buttons: Query<(&mut Visibility, &BoardIndex), With<BuildingButton>>,
+    buildings: Query<&BuildingSlot>,
+    indexed_buildings: Query<(&BuildingSlot, &BoardIndex)>,
+    board: Res<Board>,
+    players: Res<Players>,
+    hands: Res<Hands>,
+    commodities: Res<Commodities>,
+    barbarians: Res<Barbarians>,
+    home_islands: Res<HomeIslands>,
+    setup: Res<GameSetup>,
+    presets: Res<BoardPresets>,
+    rules: Res<Rules>,
+    mut turn: ResMut<Turn>,
+) {
+    if !turn.is_changed() {
+        return;
+    }
+
+    if let Turn::Steal { player, tile } = *turn {
+        let points = visible_points(
+            &indexed_buildings,
+            &board,
+            &barbarians,
+            &home_islands,
+            presets.get(&setup.board).island_bonus,
+        );
+        let victims: Vec<_> = neighbors(tile, players[player], &board, &buildings)
+            .into_iter()
+            .filter(|(_, color)| {
+                !protected(*color, &points, &setup, &rules)
+                    && (hands[*color as usize].iter().sum::<u8>() > 0
+                        || commodities[*color as usize].iter().sum::<u8>() > 0)
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        for (mut visibility, index) in buttons.iter_mut() {
+            visibility.is_visible = victims.contains(index);
+        }
+
+        if victims.is_empty() {
+            *turn = turn.next(players.len());
+        }
+    }
+}
+
+/// Take a random card from the owner of the building that the player picked
+fn steal(
+    mut commands: Commands,
+    clicked_buttons: Query<(Entity, &BoardIndex), (With<BuildingButton>, With<Clicked>)>,
+    mut buttons: Query<&mut Visibility, With<BuildingButton>>,
+    buildings: Query<&BuildingSlot>,
+    board: Res<Board>,
+    players: Res<Players>,
+    mut hands: ResMut<Hands>,
+    mut commodities: ResMut<Commodities>,
+    mut log: ResMut<GameLog>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::Steal { player, .. } = *turn {
+        for (entity, index) in clicked_buttons.iter() {
+            commands.entity(entity).remove::<Clicked>();
+
+            let thief = players[player] as usize;
+            let victim_color = buildings
+                .get(board.buildings[**index])
+                .unwrap()
+                .unwrap()
+                .color;
+            let victim = victim_color as usize;
+
+            // One entry per card, so every card is as likely to be taken
+            let cards: Vec<_> = RESOURCES
+                .into_iter()
+                .flat_map(|resource| {
+                    (0..hands[victim][resource as usize]).map(move |_| Card::Resource(resource))
+                })
+                .chain(COMMODITIES.into_iter().flat_map(|commodity| {
+                    (0..commodities[victim][commodity as usize])
+                        .map(move |_| Card::Commodity(commodity))
+                }))
+                .collect();
+
+            match cards.choose(&mut thread_rng()) {
+                Some(Card::Resource(resource)) => {
+                    hands[victim][*resource as usize] -= 1;
+                    hands[thief][*resource as usize] += 1;
+                }
+                Some(Card::Commodity(commodity)) => {
+                    commodities[victim][*commodity as usize] -= 1;
+                    commodities[thief][*commodity as usize] += 1;
+                }
+                None => (),
+            }
+
+            log.push(
+                players[player],
+                LogEvent::Stole {
+                    victim: victim_color,
+                },
+            );
+
+            for mut visibility in buttons.iter_mut() {
+                visibility.is_visible = false;
+            }
+
+            *turn = turn.next(players.len());
+        }
+    }
+}
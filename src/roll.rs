@@ -0,0 +1,248 @@
+use bevy::{prelude::*, render::camera::Camera2d};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    board::{Board, BoardIndex},
+    color::PlayerColor,
+    menu::in_game,
+    tile::Tile,
+    ui::{Die1, Die2, HandUi, UiRoot},
+};
+
+pub struct RollPlugin;
+
+impl Plugin for RollPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Roll>()
+            .init_resource::<RollTime>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(in_game)
+                    .with_system(tick_roll)
+                    .with_system(animate_dice.after(tick_roll))
+                    .with_system(highlight_tiles.after(tick_roll))
+                    .with_system(fly_cards.after(tick_roll)),
+            );
+    }
+}
+
+/// The last roll of the dice, and what came of it, so it can be played back to the players.
+/// The game has already moved on by the time it's shown.
+#[derive(Default)]
+pub struct Roll {
+    pub dice: Option<[u8; 2]>,
+    /// The tiles that produced
+    pub producing: Vec<usize>,
+    /// The tiles whose number came up, but that the robber or an earthquake kept from producing
+    pub blocked: Vec<usize>,
+    /// Every card that was handed out: the tile it came from, who got it, and its image
+    pub cards: Vec<(usize, PlayerColor, &'static str)>,
+}
+
+/// Seconds since the last roll, as of this frame and the one before
+#[derive(Default)]
+struct RollTime {
+    elapsed: f32,
+    previous: f32,
+}
+
+impl RollTime {
+    /// Whether the animation got to this point this frame
+    fn crossed(&self, time: f32) -> bool {
+        self.previous < time && time <= self.elapsed
+    }
+}
+
+/// A card on its way from the tile that produced it to its owner's hand
+#[derive(Component)]
+struct FlyingCard {
+    /// In pixels, from the bottom left of the window
+    from: Vec2,
+    color: PlayerColor,
+    /// When it takes off, in seconds since the roll
+    start: f32,
+}
+
+/// How long the dice tumble for
+const TUMBLE_TIME: f32 = 0.6;
+/// How long each face shows while the dice tumble
+const TUMBLE_FACE_TIME: f32 = 0.05;
+/// How long the tiles stay highlighted, once the dice land
+const HIGHLIGHT_TIME: f32 = 1.5;
+/// How long it takes a card to get to a hand
+const FLY_TIME: f32 = 0.8;
+/// How long each card waits after the one before it, so they can be told apart
+const CARD_STAGGER: f32 = 0.1;
+const CARD_SIZE: f32 = 32.;
+const PRODUCING_TILE_COLOR: Color = Color::rgb(1., 1., 0.6);
+const IDLE_TILE_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+const BLOCKED_TILE_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+
+fn die_image(roll: u8) -> &'static str {
+    match roll {
+        1 => "die_1.png",
+        2 => "die_2.png",
+        3 => "die_3.png",
+        4 => "die_4.png",
+        5 => "die_5.png",
+        6 => "die_6.png",
+        _ => panic!("Invalid die roll"),
+    }
+}
+
+/// Start over when there's a new roll
+fn tick_roll(roll: Res<Roll>, mut roll_time: ResMut<RollTime>, time: Res<Time>) {
+    if roll.is_changed() {
+        *roll_time = RollTime::default();
+    } else {
+        roll_time.previous = roll_time.elapsed;
+        roll_time.elapsed += time.delta_seconds();
+    }
+}
+
+/// Flip through random faces for a moment, then land on the roll
+fn animate_dice(
+    mut die_1s: Query<&mut UiImage, (With<Die1>, Without<Die2>)>,
+    mut die_2s: Query<&mut UiImage, (With<Die2>, Without<Die1>)>,
+    roll: Res<Roll>,
+    roll_time: Res<RollTime>,
+    assets: Res<AssetServer>,
+) {
+    let dice = match roll.dice {
+        Some(dice) => dice,
+        None => return,
+    };
+
+    let faces = if roll_time.elapsed < TUMBLE_TIME {
+        let face = |time: f32| (time / TUMBLE_FACE_TIME) as u32;
+        if !roll.is_changed() && face(roll_time.elapsed) == face(roll_time.previous) {
+            return;
+        }
+
+        let mut rng = thread_rng();
+        [rng.gen_range(1..=6), rng.gen_range(1..=6)]
+    } else if roll_time.crossed(TUMBLE_TIME) {
+        dice
+    } else {
+        return;
+    };
+
+    for (face, mut image) in faces
+        .into_iter()
+        .zip([die_1s.single_mut(), die_2s.single_mut()])
+    {
+        *image = assets.load(die_image(face)).into();
+    }
+}
+
+/// Once the dice land, light up the tiles that produced, and darken the ones that were blocked
+fn highlight_tiles(
+    mut tiles: Query<(&mut Sprite, &BoardIndex), With<Tile>>,
+    roll: Res<Roll>,
+    roll_time: Res<RollTime>,
+) {
+    if roll.is_changed() || roll_time.crossed(TUMBLE_TIME + HIGHLIGHT_TIME) {
+        for (mut sprite, _) in tiles.iter_mut() {
+            sprite.color = Color::WHITE;
+        }
+    }
+
+    if roll_time.crossed(TUMBLE_TIME) && !(roll.producing.is_empty() && roll.blocked.is_empty()) {
+        for (mut sprite, index) in tiles.iter_mut() {
+            sprite.color = if roll.producing.contains(index) {
+                PRODUCING_TILE_COLOR
+            } else if roll.blocked.contains(index) {
+                BLOCKED_TILE_COLOR
+            } else {
+                IDLE_TILE_COLOR
+            };
+        }
+    }
+}
+
+/// Once the dice land, send each card from its tile to its owner's hand in the sidebar
+fn fly_cards(
+    mut commands: Commands,
+    roots: Query<Entity, With<UiRoot>>,
+    mut cards: Query<(Entity, &FlyingCard, &mut Style)>,
+    hand_uis: Query<(&HandUi, &GlobalTransform)>,
+    cameras: Query<&Transform, With<Camera2d>>,
+    windows: Res<Windows>,
+    board: Res<Board>,
+    roll: Res<Roll>,
+    roll_time: Res<RollTime>,
+    assets: Res<AssetServer>,
+) {
+    // Cards from an earlier roll are done flying
+    if roll.is_changed() {
+        for (entity, _, _) in cards.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        return;
+    }
+
+    if roll_time.crossed(TUMBLE_TIME) {
+        let window = windows.get_primary().unwrap();
+        let window_size = Vec2::new(window.width(), window.height());
+        let to_screen = cameras
+            .get_single()
+            .map(|transform| transform.compute_matrix().inverse())
+            .unwrap_or_default();
+
+        for root in roots.iter() {
+            commands.entity(root).with_children(|parent| {
+                for (i, (tile, color, image)) in roll.cards.iter().enumerate() {
+                    let from = (to_screen
+                        * board.layout.tile_positions[*tile].extend(0.).extend(1.))
+                    .truncate()
+                    .truncate()
+                        + window_size / 2.;
+
+                    parent
+                        .spawn_bundle(ImageBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                position: card_position(from),
+                                size: Size::new(Val::Px(CARD_SIZE), Val::Px(CARD_SIZE)),
+                                ..default()
+                            },
+                            image: assets.load(*image).into(),
+                            ..default()
+                        })
+                        .insert(FlyingCard {
+                            from,
+                            color: *color,
+                            start: TUMBLE_TIME + i as f32 * CARD_STAGGER,
+                        });
+                }
+            });
+        }
+    }
+
+    for (entity, card, mut style) in cards.iter_mut() {
+        let progress = ((roll_time.elapsed - card.start) / FLY_TIME).clamp(0., 1.);
+        let to = hand_uis
+            .iter()
+            .find(|(hand, _)| hand.color == card.color)
+            .map(|(_, transform)| transform.translation.truncate());
+
+        match to {
+            Some(to) if progress < 1. => {
+                // Slows down as it lands
+                let eased = 1. - (1. - progress).powi(2);
+                style.position = card_position(card.from.lerp(to, eased));
+            }
+            _ => commands.entity(entity).despawn_recursive(),
+        }
+    }
+}
+
+/// Where to put a card so it's centered on the point
+fn card_position(center: Vec2) -> Rect<Val> {
+    Rect {
+        left: Val::Px(center.x - CARD_SIZE / 2.),
+        bottom: Val::Px(center.y - CARD_SIZE / 2.),
+        ..default()
+    }
+}
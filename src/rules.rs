@@ -0,0 +1,131 @@
+use std::{collections::HashMap, fs};
+
+use bevy::{asset::FileAssetIo, prelude::*};
+use serde::Deserialize;
+
+use crate::resource::{Resource, RESOURCES, RESOURCE_COUNT};
+
+/// Where the rules are kept, relative to the assets folder
+const RULES_PATH: &str = "rules.ron";
+
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Rules::load());
+    }
+}
+
+/// What something costs, as a count of each resource. Written as a map in the rules file,
+/// ex. `{Brick: 1, Lumber: 1}`.
+#[derive(Clone, Copy, Deref, Deserialize)]
+#[serde(from = "HashMap<Resource, u8>")]
+pub struct Cost([u8; RESOURCE_COUNT]);
+
+impl From<HashMap<Resource, u8>> for Cost {
+    fn from(counts: HashMap<Resource, u8>) -> Self {
+        Self(RESOURCES.map(|resource| counts.get(&resource).copied().unwrap_or(0)))
+    }
+}
+
+impl Cost {
+    fn new(counts: &[(Resource, u8)]) -> Self {
+        counts.iter().copied().collect::<HashMap<_, _>>().into()
+    }
+
+    pub fn can_afford(self, hand: [u8; RESOURCE_COUNT]) -> bool {
+        hand.into_iter()
+            .zip(self.0)
+            .all(|(have, cost)| have >= cost)
+    }
+
+    pub fn pay(self, hand: &mut [u8; RESOURCE_COUNT]) {
+        for (have, cost) in hand.iter_mut().zip(self.0) {
+            *have -= cost;
+        }
+    }
+}
+
+/// The order that players place their starting pieces in
+#[derive(Clone, Copy, Default, Deserialize, PartialEq)]
+pub enum SetupOrder {
+    /// The second round goes backwards, so the last player places twice in a row
+    #[default]
+    Snake,
+    /// Both rounds go in turn order
+    Forward,
+}
+
+/// The rules of the game. A group can change them in `assets/rules.ron` to play by their house rules.
+/// Anything left out of the file is played by the standard rules.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    pub road_cost: Cost,
+    pub settlement_cost: Cost,
+    pub city_cost: Cost,
+    pub ship_cost: Cost,
+    pub knight_cost: Cost,
+    pub activate_knight_cost: Cost,
+    pub promote_knight_cost: Cost,
+    /// How many of each piece a player has
+    pub max_roads: usize,
+    pub max_settlements: usize,
+    pub max_cities: usize,
+    pub max_ships: usize,
+    pub knights_per_level: usize,
+    pub setup_order: SetupOrder,
+    /// When a 7 is rolled, players with more resources than this discard half of them
+    pub discard_limit: u8,
+    /// Friendly robber, when it's turned on in the menu: players with this many visible victory points
+    /// or fewer can't be robbed, and the robber can't be moved next to only them
+    pub friendly_robber_points: u8,
+    /// Trade ratio at a harbor that takes any resource
+    pub harbor_ratio: u8,
+    /// Trade ratio at a harbor that takes one resource
+    pub resource_harbor_ratio: u8,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        use Resource::*;
+
+        Self {
+            road_cost: Cost::new(&[(Brick, 1), (Lumber, 1)]),
+            settlement_cost: Cost::new(&[(Brick, 1), (Wool, 1), (Grain, 1), (Lumber, 1)]),
+            city_cost: Cost::new(&[(Grain, 2), (Ore, 3)]),
+            ship_cost: Cost::new(&[(Wool, 1), (Lumber, 1)]),
+            knight_cost: Cost::new(&[(Wool, 1), (Ore, 1)]),
+            activate_knight_cost: Cost::new(&[(Grain, 1)]),
+            promote_knight_cost: Cost::new(&[(Wool, 1), (Ore, 1)]),
+            max_roads: 15,
+            max_settlements: 5,
+            max_cities: 4,
+            max_ships: 15,
+            knights_per_level: 2,
+            setup_order: default(),
+            discard_limit: 7,
+            friendly_robber_points: 2,
+            harbor_ratio: 3,
+            resource_harbor_ratio: 2,
+        }
+    }
+}
+
+impl Rules {
+    /// Reads the rules file. Without one, the game is played by the standard rules,
+    /// but a broken one is caught before anyone plays by the wrong rules.
+    fn load() -> Self {
+        let path = FileAssetIo::get_root_path().join("assets").join(RULES_PATH);
+        let file = match fs::read_to_string(&path) {
+            Ok(file) => file,
+            Err(error) => {
+                warn!("couldn't read rules from {path:?}, so using the standard rules: {error}");
+                return default();
+            }
+        };
+
+        ron::from_str(&file)
+            .unwrap_or_else(|error| panic!("couldn't parse rules {path:?}: {error}"))
+    }
+}
@@ -0,0 +1,326 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{Board, BoardIndex},
+    building::BuildingSlot,
+    button::{Clicked, RoadButton},
+    color::PlayerColor,
+    image::UpdateImages,
+    menu::in_game,
+    resource::Hands,
+    road::RoadSlot,
+    rules::Rules,
+    turn::{Players, Turn},
+    ui::{BuildShipButton, MoveShipButton},
+    undo::{Action, Placement, UndoStack},
+};
+
+pub struct ShipPlugin;
+
+impl Plugin for ShipPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShipsThisTurn>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(show_ship_buttons)
+                .with_system(build_ship)
+                .with_system(move_ship)
+                .with_system(forget_ships),
+        );
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Ship {
+    pub color: PlayerColor,
+}
+
+/// Ships go on the same edges as roads, but only the ones that touch the sea.
+/// An edge can't have both a road and a ship.
+#[derive(Clone, Component, Copy, Deref, DerefMut)]
+pub struct ShipSlot(pub Option<Ship>);
+
+impl UpdateImages for ShipSlot {
+    fn image(self, _: &Board, _: usize) -> Option<&'static str> {
+        self.map(|ship| match ship.color {
+            PlayerColor::Blue => "blue_ship.png",
+            PlayerColor::Orange => "orange_ship.png",
+            PlayerColor::Red => "red_ship.png",
+            PlayerColor::White => "white_ship.png",
+            PlayerColor::Green => "green_ship.png",
+            PlayerColor::Brown => "brown_ship.png",
+        })
+    }
+}
+
+/// What the current player has done with their ships this turn.
+/// A ship can only be moved once per turn, and not on the turn it was built.
+#[derive(Default)]
+pub struct ShipsThisTurn {
+    pub moved: bool,
+    pub built: Vec<usize>,
+}
+
+/// Whether the player may put a ship here. `ignore` is a ship that's being moved.
+/// Like roads, a ship must be next to one of their buildings, or another of their ships,
+/// but it can't continue a line of ships past another player's building.
+/// Roads and ships only connect through a building.
+pub fn can_build_ship(
+    index: usize,
+    ignore: Option<usize>,
+    color: PlayerColor,
+    board: &Board,
+    buildings: &Query<&BuildingSlot>,
+    roads: &Query<&RoadSlot>,
+    ships: &[Option<Ship>],
+) -> bool {
+    board.layout.road_sea[index]
+        && Some(index) != ignore
+        && roads.get(board.roads[index]).unwrap().is_none()
+        && ships[index].is_none()
+        && board.layout.road_buildings[index]
+            .into_iter()
+            .any(
+                |building| match **buildings.get(board.buildings[building]).unwrap() {
+                    Some(building) => building.color == color,
+                    None => has_ship(building, Some(index), ignore, color, board, ships),
+                },
+            )
+}
+
+/// Whether the player has a ship at this corner, other than the ones given
+fn has_ship(
+    building: usize,
+    except: Option<usize>,
+    ignore: Option<usize>,
+    color: PlayerColor,
+    board: &Board,
+    ships: &[Option<Ship>],
+) -> bool {
+    board.layout.building_roads[building].iter().any(|road| {
+        Some(*road) != except
+            && Some(*road) != ignore
+            && matches!(ships[*road], Some(ship) if ship.color == color)
+    })
+}
+
+/// Whether this is the player's ship, and it's at the open end of a line of ships,
+/// so it's free to be moved
+fn can_move_ship(
+    index: usize,
+    color: PlayerColor,
+    board: &Board,
+    buildings: &Query<&BuildingSlot>,
+    ships: &[Option<Ship>],
+    ships_this_turn: &ShipsThisTurn,
+) -> bool {
+    matches!(ships[index], Some(ship) if ship.color == color)
+        && !ships_this_turn.built.contains(&index)
+        && board.layout.road_buildings[index]
+            .into_iter()
+            .any(|building| {
+                buildings.get(board.buildings[building]).unwrap().is_none()
+                    && !has_ship(building, Some(index), None, color, board, ships)
+            })
+}
+
+/// Show the buttons for building a ship, or for picking a ship to move,
+/// when the player presses the button for either
+fn show_ship_buttons(
+    mut buttons: Query<(&mut Visibility, &BoardIndex), With<RoadButton>>,
+    build_buttons: Query<&Interaction, (With<BuildShipButton>, Changed<Interaction>)>,
+    move_buttons: Query<&Interaction, (With<MoveShipButton>, Changed<Interaction>)>,
+    buildings: Query<&BuildingSlot>,
+    roads: Query<&RoadSlot>,
+    ships: Query<&ShipSlot>,
+    board: Res<Board>,
+    players: Res<Players>,
+    hands: Res<Hands>,
+    rules: Res<Rules>,
+    ships_this_turn: Res<ShipsThisTurn>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::Build { player, special } = *turn {
+        let color = players[player];
+        // Every edge's ship, by board index
+        let ships: Vec<_> = board
+            .ships
+            .iter()
+            .map(|ship| **ships.get(*ship).unwrap())
+            .collect();
+        let clicked =
+            |interaction: Option<&Interaction>| matches!(interaction, Some(Interaction::Clicked));
+
+        if clicked(build_buttons.get_single().ok()) {
+            let ship_count = ships
+                .iter()
+                .filter(|ship| matches!(ship, Some(ship) if ship.color == color))
+                .count();
+
+            if rules.ship_cost.can_afford(hands[color as usize]) && ship_count < rules.max_ships {
+                let mut can_build = false;
+
+                for (mut visibility, index) in buttons.iter_mut() {
+                    let visible =
+                        can_build_ship(**index, None, color, &board, &buildings, &roads, &ships);
+
+                    visibility.is_visible = visible;
+                    can_build |= visible;
+                }
+
+                // They're charged once they pick a spot
+                if can_build {
+                    *turn = Turn::BuildShip { player, special };
+                }
+            }
+        // Ships can only be moved on the player's own turn
+        } else if clicked(move_buttons.get_single().ok())
+            && special.is_none()
+            && !ships_this_turn.moved
+        {
+            let mut can_move = false;
+
+            for (mut visibility, index) in buttons.iter_mut() {
+                let visible =
+                    can_move_ship(**index, color, &board, &buildings, &ships, &ships_this_turn);
+
+                visibility.is_visible = visible;
+                can_move |= visible;
+            }
+
+            if can_move {
+                *turn = Turn::MoveShip {
+                    player,
+                    special,
+                    from: None,
+                };
+            }
+        }
+    }
+}
+
+/// Build a ship
+fn build_ship(
+    mut commands: Commands,
+    clicked_buttons: Query<(Entity, &BoardIndex), (With<RoadButton>, With<Clicked>)>,
+    mut buttons: Query<&mut Visibility, With<RoadButton>>,
+    mut ships: Query<&mut ShipSlot>,
+    board: Res<Board>,
+    players: Res<Players>,
+    rules: Res<Rules>,
+    mut turn: ResMut<Turn>,
+    mut hands: ResMut<Hands>,
+    mut undo_stack: ResMut<UndoStack>,
+    mut ships_this_turn: ResMut<ShipsThisTurn>,
+) {
+    if let Turn::BuildShip { player, .. } = *turn {
+        for (entity, index) in clicked_buttons.iter() {
+            commands.entity(entity).remove::<Clicked>();
+
+            let color = players[player];
+            let hand = &mut hands[color as usize];
+
+            undo_stack.push(Action {
+                color,
+                hand: *hand,
+                placement: Some(Placement::Ship { index: **index }),
+            });
+
+            rules.ship_cost.pay(hand);
+
+            **ships.get_mut(board.ships[**index]).unwrap() = Some(Ship { color });
+            ships_this_turn.built.push(**index);
+
+            for mut visibility in buttons.iter_mut() {
+                visibility.is_visible = false;
+            }
+
+            *turn = turn.next(players.len());
+        }
+    }
+}
+
+/// Pick a ship to move, and then where to move it
+fn move_ship(
+    mut commands: Commands,
+    clicked_buttons: Query<(Entity, &BoardIndex), (With<RoadButton>, With<Clicked>)>,
+    mut buttons: Query<(&mut Visibility, &BoardIndex), With<RoadButton>>,
+    buildings: Query<&BuildingSlot>,
+    roads: Query<&RoadSlot>,
+    mut ships: Query<&mut ShipSlot>,
+    board: Res<Board>,
+    players: Res<Players>,
+    hands: Res<Hands>,
+    mut turn: ResMut<Turn>,
+    mut undo_stack: ResMut<UndoStack>,
+    mut ships_this_turn: ResMut<ShipsThisTurn>,
+) {
+    if let Turn::MoveShip {
+        player,
+        special,
+        from,
+    } = *turn
+    {
+        let color = players[player];
+
+        for (entity, index) in clicked_buttons.iter() {
+            commands.entity(entity).remove::<Clicked>();
+
+            match from {
+                // The player picked the ship, so show where it can go
+                None => {
+                    let ships: Vec<_> = board
+                        .ships
+                        .iter()
+                        .map(|ship| **ships.get(*ship).unwrap())
+                        .collect();
+
+                    for (mut visibility, other) in buttons.iter_mut() {
+                        visibility.is_visible = can_build_ship(
+                            **other,
+                            Some(**index),
+                            color,
+                            &board,
+                            &buildings,
+                            &roads,
+                            &ships,
+                        );
+                    }
+
+                    *turn = Turn::MoveShip {
+                        player,
+                        special,
+                        from: Some(**index),
+                    };
+                }
+                // The player picked where it goes
+                Some(from) => {
+                    undo_stack.push(Action {
+                        color,
+                        hand: hands[color as usize],
+                        placement: Some(Placement::MoveShip { from, to: **index }),
+                    });
+
+                    **ships.get_mut(board.ships[from]).unwrap() = None;
+                    **ships.get_mut(board.ships[**index]).unwrap() = Some(Ship { color });
+                    ships_this_turn.moved = true;
+
+                    for (mut visibility, _) in buttons.iter_mut() {
+                        visibility.is_visible = false;
+                    }
+
+                    *turn = turn.next(players.len());
+                }
+            }
+        }
+    }
+}
+
+/// Start each turn with a fresh ship move
+fn forget_ships(turn: Res<Turn>, mut ships_this_turn: ResMut<ShipsThisTurn>) {
+    if turn.is_changed() {
+        if let Turn::Roll { .. } = *turn {
+            *ships_this_turn = default();
+        }
+    }
+}
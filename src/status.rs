@@ -1,6 +1,11 @@
 use bevy::prelude::*;
 
 use crate::{
+    discard::Discards,
+    gold::GoldChoices,
+    knight::KnightAction,
+    menu::{in_game, GameSetup, Ruleset},
+    rules::Rules,
     turn::{Players, Turn},
     ui::StatusBar,
 };
@@ -9,7 +14,7 @@ pub struct StatusPlugin;
 
 impl Plugin for StatusPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(update_status);
+        app.add_system(update_status.with_run_criteria(in_game));
     }
 }
 
@@ -18,28 +23,122 @@ fn update_status(
     mut status_bars: Query<&mut Text, With<StatusBar>>,
     turn: Res<Turn>,
     players: Res<Players>,
+    setup: Res<GameSetup>,
+    gold_choices: Res<GoldChoices>,
+    discards: Res<Discards>,
+    rules: Res<Rules>,
 ) {
-    if turn.is_changed() {
+    if turn.is_changed() || gold_choices.is_changed() || discards.is_changed() {
+        let name = |player: usize| &setup.seat(players[player]).name;
+
         for mut text in status_bars.iter_mut() {
             text.sections[0].value = match *turn {
                 Turn::Setup {
-                    round_2,
+                    round,
                     player,
                     road,
+                    ..
                 } => format!(
-                    "Setup round {}: {}: build a {}",
-                    if round_2 { "2" } else { "1" },
-                    String::from(players[player]),
-                    if road { "road" } else { "settlement" }
+                    "Setup round {round}: {}: build a {}",
+                    name(player),
+                    match (road, round, setup.ruleset) {
+                        (true, ..) => "road",
+                        (false, 2, Ruleset::CitiesAndKnights) => "city",
+                        (false, ..) => "settlement",
+                    }
                 ),
-                Turn::Production { player } | Turn::Build { player } => {
-                    format!("{}: build and trade", String::from(players[player]))
+                Turn::Draft { player } => {
+                    format!("Draft: {}: pick your place in the turn order", name(player))
+                }
+                Turn::Roll { player } => {
+                    format!("{}: roll the dice", name(player))
+                }
+                Turn::Production { player }
+                | Turn::Build {
+                    player,
+                    special: None,
+                } => {
+                    format!("{}: build and trade", name(player))
+                }
+                Turn::Build {
+                    player,
+                    special: Some(special),
+                } => {
+                    format!(
+                        "Special building phase after {}: {}: build, but don't trade",
+                        name(special),
+                        name(player)
+                    )
                 }
-                Turn::BuildRoad { player } => {
-                    format!("{}: build a road", String::from(players[player]))
+                Turn::Discard { discarder, .. } => {
+                    let count = discards[players[discarder] as usize];
+
+                    format!(
+                        "{}: you have too many cards, so discard {count} resource{}",
+                        name(discarder),
+                        if count == 1 { "" } else { "s" }
+                    )
+                }
+                Turn::MoveRobber { player } if setup.friendly_robber => format!(
+                    "{}: move the robber, but not next to only players with {} VP or less",
+                    name(player),
+                    rules.friendly_robber_points
+                ),
+                Turn::MoveRobber { player } => {
+                    format!("{}: move the robber", name(player))
+                }
+                Turn::Steal { player, .. } if setup.friendly_robber => format!(
+                    "{}: pick a player to steal from. Players with {} VP or less are safe.",
+                    name(player),
+                    rules.friendly_robber_points
+                ),
+                Turn::Steal { player, .. } => {
+                    format!("{}: pick a player to steal from", name(player))
                 }
-                Turn::BuildSettlement { player } => {
-                    format!("{}: build a settlement", String::from(players[player]))
+                Turn::ChooseResource { chooser, .. } => {
+                    let count = gold_choices[players[chooser] as usize];
+
+                    format!(
+                        "{}: choose {count} resource{}",
+                        name(chooser),
+                        if count == 1 { "" } else { "s" }
+                    )
+                }
+                Turn::BuildRoad { player, .. } => {
+                    format!("{}: build a road, or cancel", name(player))
+                }
+                Turn::BuildSettlement { player, .. } => {
+                    format!("{}: build a settlement, or cancel", name(player))
+                }
+                Turn::BuildCity { player, .. } => {
+                    format!("{}: build a city, or cancel", name(player))
+                }
+                Turn::BuildShip { player, .. } => {
+                    format!("{}: build a ship, or cancel", name(player))
+                }
+                Turn::MoveShip {
+                    player, from: None, ..
+                } => {
+                    format!("{}: pick a ship to move, or cancel", name(player))
+                }
+                Turn::MoveShip {
+                    player,
+                    from: Some(_),
+                    ..
+                } => {
+                    format!("{}: move the ship, or cancel", name(player))
+                }
+                Turn::Knight { player, action, .. } => format!(
+                    "{}: {} a knight, or cancel",
+                    name(player),
+                    match action {
+                        KnightAction::Build => "build",
+                        KnightAction::Activate => "activate",
+                        KnightAction::Promote => "promote",
+                    }
+                ),
+                Turn::ImproveCity { player, .. } => {
+                    format!("{}: pick a city improvement, or cancel", name(player))
                 }
                 Turn::Done => "Game over".to_string(),
             }
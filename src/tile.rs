@@ -1,8 +1,9 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
-use crate::{image::UpdateImages, random::Shuffle, resource::Resource};
+use crate::{board::Board, image::UpdateImages, random::Shuffle, resource::Resource};
 
-#[derive(Clone, Component, Copy, Debug)]
+#[derive(Clone, Component, Copy, Debug, Deserialize)]
 pub enum Tile {
     Hills,
     Pasture,
@@ -10,9 +11,14 @@ pub enum Tile {
     Fields,
     Forest,
     Desert,
+    Sea,
+    /// Produces any resource
+    Gold,
+    /// Burns down the buildings around it
+    Volcano,
 }
 
-/// Every `Tile` variant
+/// Every `Tile` variant that can be picked for a land hex
 static TILES: &[Tile] = &[
     Tile::Hills,
     Tile::Pasture,
@@ -32,34 +38,56 @@ impl Shuffle for Tile {
             Self::Hills | Self::Mountains => 3.,
             Self::Pasture | Self::Fields | Self::Forest => 4.,
             Self::Desert => 1.,
+            Self::Sea | Self::Gold | Self::Volcano => 0.,
         }
     }
 }
 
+/// What a tile does to the buildings around it when its number is rolled.
+/// To add a new kind of tile, give it an effect here, and handle any new effect in `produce_resources`.
+#[derive(Clone, Copy)]
+pub enum TileEffect {
+    /// Each building gets this resource
+    Produce(Resource),
+    /// Each building's owner gets to choose a resource
+    ChooseResource,
+    /// Each building is knocked down: cities to settlements, and settlements off the board
+    Erupt,
+}
+
 impl Tile {
-    /// What resource (if any) does this tile produce?
-    pub fn resource(self) -> Option<Resource> {
+    /// What happens when this tile's number is rolled, if it has a number
+    pub fn effect(self) -> Option<TileEffect> {
         match self {
-            Self::Hills => Some(Resource::Brick),
-            Self::Pasture => Some(Resource::Wool),
-            Self::Mountains => Some(Resource::Ore),
-            Self::Fields => Some(Resource::Grain),
-            Self::Forest => Some(Resource::Lumber),
-            Self::Desert => None,
+            Self::Hills => Some(TileEffect::Produce(Resource::Brick)),
+            Self::Pasture => Some(TileEffect::Produce(Resource::Wool)),
+            Self::Mountains => Some(TileEffect::Produce(Resource::Ore)),
+            Self::Fields => Some(TileEffect::Produce(Resource::Grain)),
+            Self::Forest => Some(TileEffect::Produce(Resource::Lumber)),
+            Self::Gold => Some(TileEffect::ChooseResource),
+            Self::Volcano => Some(TileEffect::Erupt),
+            Self::Desert | Self::Sea => None,
         }
     }
 
     /// Does the robber spawn here?
     pub fn robber_home(self) -> bool {
         match self {
-            Self::Hills | Self::Pasture | Self::Mountains | Self::Fields | Self::Forest => false,
+            Self::Hills
+            | Self::Pasture
+            | Self::Mountains
+            | Self::Fields
+            | Self::Forest
+            | Self::Sea
+            | Self::Gold
+            | Self::Volcano => false,
             Self::Desert => true,
         }
     }
 }
 
 impl UpdateImages for Tile {
-    fn image(self, _: usize) -> Option<&'static str> {
+    fn image(self, _: &Board, _: usize) -> Option<&'static str> {
         match self {
             Self::Hills => Some("hills.png"),
             Self::Pasture => Some("pasture.png"),
@@ -67,6 +95,9 @@ impl UpdateImages for Tile {
             Self::Fields => Some("fields.png"),
             Self::Forest => Some("forest.png"),
             Self::Desert => Some("desert.png"),
+            Self::Sea => Some("sea.png"),
+            Self::Gold => Some("gold.png"),
+            Self::Volcano => Some("volcano.png"),
         }
     }
 }
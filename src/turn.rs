@@ -2,8 +2,13 @@ use bevy::prelude::*;
 use rand::{prelude::SliceRandom, thread_rng};
 
 use crate::{
+    button::{BuildingButton, RoadButton},
     color::{PlayerColor, COLORS},
-    ui::NextButton,
+    hot_seat::PassTo,
+    knight::KnightAction,
+    menu::in_game,
+    rules::SetupOrder,
+    ui::{CancelButton, NextButton, RollButton},
 };
 
 pub struct TurnPlugin;
@@ -12,44 +17,138 @@ impl Plugin for TurnPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<Turn>()
             .init_resource::<Players>()
-            .add_system(press_next_button);
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(in_game)
+                    .with_system(press_roll_button)
+                    .with_system(press_next_button)
+                    .with_system(cancel_build),
+            );
     }
 }
 
-pub const PLAYER_COUNT: usize = 4;
-const LAST_PLAYER: usize = PLAYER_COUNT - 1;
-
 /// Represents the turn order
-#[derive(Clone, Copy, Deref)]
-pub struct Players([PlayerColor; PLAYER_COUNT]);
+#[derive(Clone, Deref)]
+pub struct Players(Vec<PlayerColor>);
 
-impl Default for Players {
-    fn default() -> Self {
-        let mut players = COLORS;
+impl Players {
+    /// Seats the given players in a random turn order
+    pub fn shuffled(mut players: Vec<PlayerColor>) -> Self {
         players.shuffle(&mut thread_rng());
         Players(players)
     }
+
+    /// Changes the turn order, so the player at each index of `order` takes that place
+    pub fn reseat(&mut self, order: &[usize]) {
+        self.0 = order.iter().map(|&player| self.0[player]).collect();
+    }
+}
+
+impl Default for Players {
+    fn default() -> Self {
+        Self::shuffled(COLORS.to_vec())
+    }
+}
+
+/// With at least this many players, after each player's turn,
+/// every other player gets a chance to build, in turn order
+const SPECIAL_BUILD_PLAYERS: usize = 5;
+
+/// Whether this round of setup goes backwards through the turn order.
+/// A third round goes forwards again, so the first player places twice in a row between rounds 2 and 3,
+/// just like the last player does between rounds 1 and 2. That's intended.
+fn setup_reversed(round: u8, order: SetupOrder) -> bool {
+    round == 2 && order == SetupOrder::Snake
+}
+
+/// The first and last players to place in this round of setup
+fn setup_ends(round: u8, order: SetupOrder, last_player: usize) -> (usize, usize) {
+    if setup_reversed(round, order) {
+        (last_player, 0)
+    } else {
+        (0, last_player)
+    }
 }
 
 /// Represents what phase we're in
 #[derive(Clone, Copy)]
 pub enum Turn {
+    /// Before setup, `player` is picking their place in the turn order
+    Draft {
+        player: usize,
+    },
+    /// `round` counts from 1 up to `rounds`, which is 2, or 3 in the third settlement variant
     Setup {
-        round_2: bool,
+        round: u8,
+        rounds: u8,
         player: usize,
         road: bool,
+        order: SetupOrder,
+    },
+    /// The player may do things before they roll, but can't build or trade yet
+    Roll {
+        player: usize,
     },
     Production {
         player: usize,
     },
+    /// The player rolled a 7, and `discarder` is discarding half their resources,
+    /// since they had more than the rules allow
+    Discard {
+        player: usize,
+        discarder: usize,
+    },
+    /// The player rolled a 7, and is moving the robber
+    MoveRobber {
+        player: usize,
+    },
+    /// The player is picking someone next to the robber's new `tile` to steal from
+    Steal {
+        player: usize,
+        tile: usize,
+    },
+    /// `chooser` is choosing the resources they got from gold fields
+    ChooseResource {
+        player: usize,
+        chooser: usize,
+    },
+    /// During the special building phase, `special` is the player whose turn it is,
+    /// and `player` is the player building
     Build {
         player: usize,
+        special: Option<usize>,
     },
     BuildRoad {
         player: usize,
+        special: Option<usize>,
     },
     BuildSettlement {
         player: usize,
+        special: Option<usize>,
+    },
+    BuildCity {
+        player: usize,
+        special: Option<usize>,
+    },
+    BuildShip {
+        player: usize,
+        special: Option<usize>,
+    },
+    /// `from` is the ship being moved, once the player has picked one
+    MoveShip {
+        player: usize,
+        special: Option<usize>,
+        from: Option<usize>,
+    },
+    /// Building, activating, or promoting a knight
+    Knight {
+        player: usize,
+        special: Option<usize>,
+        action: KnightAction,
+    },
+    ImproveCity {
+        player: usize,
+        special: Option<usize>,
     },
     Done,
 }
@@ -57,90 +156,329 @@ pub enum Turn {
 impl Default for Turn {
     fn default() -> Self {
         Self::Setup {
-            round_2: false,
+            round: 1,
+            rounds: 2,
             player: 0,
             road: false,
+            order: default(),
         }
     }
 }
 
 impl Turn {
+    /// Gets the index of the player whose turn it is, if any
+    pub fn player(self) -> Option<usize> {
+        match self {
+            Self::Draft { player }
+            | Self::Setup { player, .. }
+            | Self::Roll { player }
+            | Self::Production { player }
+            | Self::Discard {
+                discarder: player, ..
+            }
+            | Self::MoveRobber { player }
+            | Self::Steal { player, .. }
+            | Self::ChooseResource {
+                chooser: player, ..
+            }
+            | Self::Build { player, .. }
+            | Self::BuildRoad { player, .. }
+            | Self::BuildSettlement { player, .. }
+            | Self::BuildCity { player, .. }
+            | Self::BuildShip { player, .. }
+            | Self::MoveShip { player, .. }
+            | Self::Knight { player, .. }
+            | Self::ImproveCity { player, .. } => Some(player),
+            Self::Done => None,
+        }
+    }
+
     /// Gets the default next `Turn`
-    pub fn next(self) -> Self {
+    pub fn next(self, player_count: usize) -> Self {
+        let last_player = player_count - 1;
+
         match self {
             // Done building a settlement in setup phase
             Self::Setup {
-                round_2,
+                round,
+                rounds,
                 player,
                 road: false,
+                order,
             } => Self::Setup {
-                round_2,
+                round,
+                rounds,
                 player,
                 road: true,
+                order,
             },
-            // Done with round 1 of setup
+            // Done with the last round of setup
             Self::Setup {
-                round_2: false,
-                player: LAST_PLAYER,
+                round,
+                rounds,
+                player,
                 road: true,
-            } => Self::Setup {
-                round_2: true,
-                player: LAST_PLAYER,
-                road: false,
-            },
-            // Player is done with their setup turn
+                order,
+            } if round == rounds && player == setup_ends(round, order, last_player).1 => {
+                Self::Roll { player: 0 }
+            }
+            // Done with a round of setup. In snake order, the last player goes again to start round 2.
             Self::Setup {
-                round_2: false,
+                round,
+                rounds,
                 player,
                 road: true,
-            } => Self::Setup {
-                round_2: false,
-                player: player + 1,
+                order,
+            } if player == setup_ends(round, order, last_player).1 => Self::Setup {
+                round: round + 1,
+                rounds,
+                player: setup_ends(round + 1, order, last_player).0,
                 road: false,
+                order,
             },
-            // Done with round 2 of setup
-            Self::Setup {
-                round_2: true,
-                player: 0,
-                road: true,
-            }
-            // Done with a full round of gameplay
-            | Self::Build {
-                player: LAST_PLAYER,
-            } => Self::Production { player: 0 },
-            // Player is done with their setup turn in round 2, which advances backwards
+            // Player is done with their setup turn
             Self::Setup {
-                round_2: true,
+                round,
+                rounds,
                 player,
                 road: true,
+                order,
             } => Self::Setup {
-                round_2: true,
-                player: player - 1,
+                round,
+                rounds,
+                player: if setup_reversed(round, order) {
+                    player - 1
+                } else {
+                    player + 1
+                },
                 road: false,
+                order,
+            },
+            // Picked a place in the turn order
+            Self::Draft { player } => Self::Draft { player: player + 1 },
+            // Rolled the dice
+            Self::Roll { player } => Self::Production { player },
+            // Done with production, and everyone's chosen their resources from gold fields
+            Self::Production { player } | Self::ChooseResource { player, .. } => Self::Build {
+                player,
+                special: None,
+            },
+            // Everyone with too many cards has discarded
+            Self::Discard { player, .. } => Self::MoveRobber { player },
+            // Done with the robber
+            Self::MoveRobber { player } | Self::Steal { player, .. } => Self::Build {
+                player,
+                special: None,
+            },
+            // Done with build mode, and there are enough players for a special building phase
+            Self::Build {
+                player,
+                special: None,
+            } if player_count >= SPECIAL_BUILD_PLAYERS => Self::Build {
+                player: (player + 1) % player_count,
+                special: Some(player),
             },
-            // Done with production
-            Self::Production { player } => Self::Build { player },
             // Done with build mode
-            Self::Build { player } => Self::Production { player: player + 1 },
+            Self::Build {
+                player,
+                special: None,
+            } => Self::Roll {
+                player: (player + 1) % player_count,
+            },
+            // Everyone else has had a chance to build, so it's the next player's turn
+            Self::Build {
+                player,
+                special: Some(special),
+            } if (player + 1) % player_count == special => Self::Roll {
+                player: (special + 1) % player_count,
+            },
+            // The next player gets their chance to build
+            Self::Build {
+                player,
+                special: Some(special),
+            } => Self::Build {
+                player: (player + 1) % player_count,
+                special: Some(special),
+            },
             // Finished building a road
-            Self::BuildRoad { player } => Self::Build { player },
+            Self::BuildRoad { player, special } => Self::Build { player, special },
             // Finished building a settlement
-            Self::BuildSettlement { player } => Self::Build { player },
+            Self::BuildSettlement { player, special } => Self::Build { player, special },
+            // Finished building a city
+            Self::BuildCity { player, special } => Self::Build { player, special },
+            // Finished building a ship
+            Self::BuildShip { player, special } => Self::Build { player, special },
+            // Finished moving a ship
+            Self::MoveShip {
+                player, special, ..
+            } => Self::Build { player, special },
+            // Finished with a knight
+            Self::Knight {
+                player, special, ..
+            } => Self::Build { player, special },
+            // Finished improving a city
+            Self::ImproveCity { player, special } => Self::Build { player, special },
             Self::Done => Self::Done,
         }
     }
 }
 
+/// If it's time to roll, and the roll button or Space is pressed, roll the dice
+fn press_roll_button(
+    buttons: Query<&Interaction, (With<RollButton>, Changed<Interaction>)>,
+    keys: Res<Input<KeyCode>>,
+    players: Res<Players>,
+    pass_to: Res<PassTo>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::Roll { .. } = *turn {
+        // The pass screen blocks the button, but not the keyboard
+        if (keys.just_pressed(KeyCode::Space) && pass_to.is_none())
+            || buttons
+                .iter()
+                .any(|interaction| matches!(interaction, Interaction::Clicked))
+        {
+            *turn = turn.next(players.len());
+        }
+    }
+}
+
 /// If in a phase that allows the next button, if the button is pressed, advance the turn
 fn press_next_button(
     buttons: Query<&Interaction, (With<NextButton>, Changed<Interaction>)>,
+    players: Res<Players>,
     mut turn: ResMut<Turn>,
 ) {
     if let Turn::Build { .. } = *turn {
         for interaction in buttons.iter() {
             if let Interaction::Clicked = interaction {
-                *turn = turn.next();
+                *turn = turn.next(players.len());
+            }
+        }
+    }
+}
+
+/// If the player is picking a spot to build, and they press the cancel button or Escape,
+/// hide the build buttons and go back to build mode. They haven't been charged yet.
+fn cancel_build(
+    buttons: Query<&Interaction, (With<CancelButton>, Changed<Interaction>)>,
+    mut build_buttons: Query<&mut Visibility, Or<(With<BuildingButton>, With<RoadButton>)>>,
+    keys: Res<Input<KeyCode>>,
+    mut turn: ResMut<Turn>,
+) {
+    if let Turn::BuildRoad { player, special }
+    | Turn::BuildSettlement { player, special }
+    | Turn::BuildCity { player, special }
+    | Turn::BuildShip { player, special }
+    | Turn::MoveShip {
+        player, special, ..
+    }
+    | Turn::Knight {
+        player, special, ..
+    }
+    | Turn::ImproveCity { player, special } = *turn
+    {
+        if keys.just_pressed(KeyCode::Escape)
+            || buttons
+                .iter()
+                .any(|interaction| matches!(interaction, Interaction::Clicked))
+        {
+            for mut visibility in build_buttons.iter_mut() {
+                visibility.is_visible = false;
             }
+
+            *turn = Turn::Build { player, special };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Walks setup from the start, and gets who placed each settlement, in order,
+    /// checking that each player places a road right after their settlement
+    fn setup_placements(player_count: usize, rounds: u8, order: SetupOrder) -> Vec<usize> {
+        let mut turn = Turn::Setup {
+            round: 1,
+            rounds,
+            player: 0,
+            road: false,
+            order,
+        };
+        let mut placements = Vec::new();
+
+        loop {
+            match turn {
+                Turn::Setup {
+                    player,
+                    road: false,
+                    ..
+                } => {
+                    placements.push(player);
+                    turn = turn.next(player_count);
+                    assert!(matches!(
+                        turn,
+                        Turn::Setup { player: road_player, road: true, .. } if road_player == player
+                    ));
+                    turn = turn.next(player_count);
+                }
+                Turn::Roll { player } => {
+                    assert_eq!(player, 0);
+                    return placements;
+                }
+                _ => panic!("setup led somewhere other than the first roll"),
+            }
+        }
+    }
+
+    #[test]
+    fn snake_setup() {
+        assert_eq!(
+            setup_placements(3, 2, SetupOrder::Snake),
+            [0, 1, 2, 2, 1, 0]
+        );
+        assert_eq!(
+            setup_placements(4, 2, SetupOrder::Snake),
+            [0, 1, 2, 3, 3, 2, 1, 0]
+        );
+        assert_eq!(
+            setup_placements(3, 3, SetupOrder::Snake),
+            [0, 1, 2, 2, 1, 0, 0, 1, 2]
+        );
+        assert_eq!(
+            setup_placements(4, 3, SetupOrder::Snake),
+            [0, 1, 2, 3, 3, 2, 1, 0, 0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn forward_setup() {
+        assert_eq!(
+            setup_placements(3, 2, SetupOrder::Forward),
+            [0, 1, 2, 0, 1, 2]
+        );
+        assert_eq!(
+            setup_placements(4, 2, SetupOrder::Forward),
+            [0, 1, 2, 3, 0, 1, 2, 3]
+        );
+        assert_eq!(
+            setup_placements(3, 3, SetupOrder::Forward),
+            [0, 1, 2, 0, 1, 2, 0, 1, 2]
+        );
+        assert_eq!(
+            setup_placements(4, 3, SetupOrder::Forward),
+            [0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn draft_goes_in_seat_order() {
+        let mut turn = Turn::Draft { player: 0 };
+
+        for expected in 1..4 {
+            turn = turn.next(4);
+            assert!(matches!(turn, Turn::Draft { player } if player == expected));
         }
     }
 }
@@ -1,46 +1,164 @@
 use bevy::{prelude::*, ui::FocusPolicy};
 
-use crate::{color::PlayerColor, turn::Players};
+use crate::{
+    color::PlayerColor,
+    cost_card::Purchase,
+    knight::KnightAction,
+    menu::{start_game, starting_game, GameSetup, Ruleset},
+    preset::BoardPresets,
+    turn::Players,
+};
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(init_ui);
+        app.add_system(init_ui.with_run_criteria(starting_game).after(start_game));
     }
 }
 
-/// Marks an entity as displaying what resources a player has, via children
+/// Marks an entity as displaying what cards and victory points a player has, via children
 #[derive(Component)]
 pub struct HandUi {
     pub color: PlayerColor,
 }
 
+/// Marks a player's name in the UI. The second section is for bonus victory points from islands,
+/// and the third is for victory points from defending against the barbarians.
+#[derive(Component)]
+pub struct PlayerHeading {
+    pub color: PlayerColor,
+}
+
+#[derive(Component)]
+pub struct RollButton;
+
 #[derive(Component)]
 pub struct NextButton;
 
 #[derive(Component)]
 pub struct BuildRoadButton;
 
+/// Marks a player's city improvement levels in the UI, in Cities & Knights
+#[derive(Component)]
+pub struct ImprovementUi {
+    pub color: PlayerColor,
+}
+
 #[derive(Component)]
 pub struct BuildSettlementButton;
 
+#[derive(Component)]
+pub struct BuildCityButton;
+
+#[derive(Component)]
+pub struct BuildShipButton;
+
+#[derive(Component)]
+pub struct MoveShipButton;
+
+#[derive(Component)]
+pub struct BuildKnightButton;
+
+#[derive(Component)]
+pub struct ActivateKnightButton;
+
+#[derive(Component)]
+pub struct PromoteKnightButton;
+
+#[derive(Component)]
+pub struct ImproveCityButton;
+
+#[derive(Component)]
+pub struct CancelButton;
+
+#[derive(Component)]
+pub struct UndoButton;
+
+/// Shows or hides the production odds overlay
+#[derive(Component)]
+pub struct OverlayButton;
+
+/// Fits the whole board back into view, after zooming or panning
+#[derive(Component)]
+pub struct FitBoardButton;
+
+#[derive(Component)]
+pub struct RestartButton;
+
+#[derive(Component)]
+pub struct QuitButton;
+
 #[derive(Component)]
 pub struct Die1;
 
 #[derive(Component)]
 pub struct Die2;
 
+/// The event die, in Cities & Knights
+#[derive(Component)]
+pub struct EventDieUi;
+
+/// How many event cards are left, and the last event, when playing with the event deck
+#[derive(Component)]
+pub struct DeckText;
+
+/// How close the barbarians are, in Cities & Knights
+#[derive(Component)]
+pub struct BarbarianText;
+
 #[derive(Component)]
 pub struct StatusBar;
 
+/// Holds the buttons for choosing a resource, when there's a choice to make
+#[derive(Component)]
+pub struct ChoiceBar;
+
+/// Lists what each piece costs
+#[derive(Component)]
+pub struct CostCard;
+
+/// Holds the game log, and scrolls it when hovered
+#[derive(Component)]
+pub struct LogUi;
+
+/// The entries of the game log that are scrolled to
+#[derive(Component)]
+pub struct LogText;
+
+/// Picks whose entries the game log shows
+#[derive(Component)]
+pub struct LogFilterButton;
+
+/// Saves the game log to a text file
+#[derive(Component)]
+pub struct ExportLogButton;
+
+/// Follows the cursor, and shows what the hovered corner would yield
+#[derive(Component)]
+pub struct Tooltip;
+
+/// Marks the node that all of the game's UI is under
+#[derive(Component)]
+pub struct UiRoot;
+
 const PLAYER_HEADING_SIZE: f32 = 50.;
-const BUTTON_FONT_SIZE: f32 = 30.;
+pub const BUTTON_FONT_SIZE: f32 = 30.;
 const STATUS_FONT_SIZE: f32 = 40.;
-const UI_BACKGROUND_COLOR: Color = Color::rgb(0.024, 0., 0.275);
+const IMPROVEMENT_FONT_SIZE: f32 = 20.;
+const DECK_FONT_SIZE: f32 = 20.;
+const TOOLTIP_FONT_SIZE: f32 = 20.;
+const LOG_FONT_SIZE: f32 = 16.;
+pub const UI_BACKGROUND_COLOR: Color = Color::rgb(0.024, 0., 0.275);
 
 /// Setup the game's UI
-fn init_ui(mut commands: Commands, players: Res<Players>, assets: Res<AssetServer>) {
+fn init_ui(
+    mut commands: Commands,
+    players: Res<Players>,
+    setup: Res<GameSetup>,
+    presets: Res<BoardPresets>,
+    assets: Res<AssetServer>,
+) {
     // Root node
     commands
         .spawn_bundle(NodeBundle {
@@ -52,6 +170,7 @@ fn init_ui(mut commands: Commands, players: Res<Players>, assets: Res<AssetServe
             color: Color::NONE.into(),
             ..default()
         })
+        .insert(UiRoot)
         .with_children(|parent| {
             // Left sidebar
             parent
@@ -66,33 +185,152 @@ fn init_ui(mut commands: Commands, players: Res<Players>, assets: Res<AssetServe
                     ..default()
                 })
                 .with_children(|parent| {
-                    for player in players.into_iter() {
+                    for &player in players.iter() {
                         // Player heading
-                        parent.spawn_bundle(TextBundle {
-                            text: Text::with_section(
-                                player,
-                                TextStyle {
-                                    font: assets.load("FiraSans-Bold.ttf"),
-                                    font_size: PLAYER_HEADING_SIZE,
-                                    color: player.into(),
+                        let style = TextStyle {
+                            font: assets.load("FiraSans-Bold.ttf"),
+                            font_size: PLAYER_HEADING_SIZE,
+                            color: player.into(),
+                        };
+
+                        parent
+                            .spawn_bundle(TextBundle {
+                                text: Text {
+                                    sections: vec![
+                                        TextSection {
+                                            value: setup.seat(player).name.clone(),
+                                            style: style.clone(),
+                                        },
+                                        TextSection {
+                                            value: String::new(),
+                                            style: style.clone(),
+                                        },
+                                        TextSection {
+                                            value: String::new(),
+                                            style,
+                                        },
+                                    ],
+                                    ..default()
                                 },
-                                default(),
-                            ),
-                            ..default()
-                        });
+                                ..default()
+                            })
+                            .insert(PlayerHeading { color: player });
 
                         // Player hand UI
                         parent
                             .spawn_bundle(NodeBundle {
                                 style: Style {
                                     size: Size::new(Val::Percent(100.), Val::Auto),
+                                    flex_wrap: FlexWrap::Wrap,
+                                    align_items: AlignItems::Center,
                                     ..default()
                                 },
                                 color: Color::NONE.into(),
                                 ..default()
                             })
                             .insert(HandUi { color: player });
+
+                        // City improvement levels
+                        if setup.ruleset == Ruleset::CitiesAndKnights {
+                            parent
+                                .spawn_bundle(TextBundle {
+                                    text: Text::with_section(
+                                        "",
+                                        TextStyle {
+                                            font: assets.load("FiraSans-Bold.ttf"),
+                                            font_size: IMPROVEMENT_FONT_SIZE,
+                                            color: default(),
+                                        },
+                                        default(),
+                                    ),
+                                    ..default()
+                                })
+                                .insert(ImprovementUi { color: player });
+                        }
                     }
+
+                    // Game log
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(100.), Val::Auto),
+                                flex_direction: FlexDirection::ColumnReverse,
+                                align_items: AlignItems::FlexStart,
+                                ..default()
+                            },
+                            color: Color::NONE.into(),
+                            ..default()
+                        })
+                        .insert(LogUi)
+                        .insert(Interaction::default())
+                        .with_children(|parent| {
+                            // Log buttons
+                            parent
+                                .spawn_bundle(NodeBundle {
+                                    color: Color::NONE.into(),
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    // Filter button
+                                    parent
+                                        .spawn_bundle(ButtonBundle::default())
+                                        .insert(LogFilterButton)
+                                        .with_children(|parent| {
+                                            // Button text
+                                            parent.spawn_bundle(TextBundle {
+                                                text: Text::with_section(
+                                                    "Everyone",
+                                                    TextStyle {
+                                                        font: assets.load("FiraSans-Bold.ttf"),
+                                                        font_size: BUTTON_FONT_SIZE,
+                                                        color: Color::BLACK,
+                                                    },
+                                                    default(),
+                                                ),
+                                                focus_policy: FocusPolicy::Pass,
+                                                ..default()
+                                            });
+                                        });
+
+                                    // Export button
+                                    parent
+                                        .spawn_bundle(ButtonBundle::default())
+                                        .insert(ExportLogButton)
+                                        .with_children(|parent| {
+                                            // Button text
+                                            parent.spawn_bundle(TextBundle {
+                                                text: Text::with_section(
+                                                    "Export",
+                                                    TextStyle {
+                                                        font: assets.load("FiraSans-Bold.ttf"),
+                                                        font_size: BUTTON_FONT_SIZE,
+                                                        color: Color::BLACK,
+                                                    },
+                                                    default(),
+                                                ),
+                                                focus_policy: FocusPolicy::Pass,
+                                                ..default()
+                                            });
+                                        });
+                                });
+
+                            // Log text
+                            parent
+                                .spawn_bundle(TextBundle {
+                                    text: Text::with_section(
+                                        "",
+                                        TextStyle {
+                                            font: assets.load("FiraSans-Bold.ttf"),
+                                            font_size: LOG_FONT_SIZE,
+                                            color: default(),
+                                        },
+                                        default(),
+                                    ),
+                                    focus_policy: FocusPolicy::Pass,
+                                    ..default()
+                                })
+                                .insert(LogText);
+                        });
                 });
 
             // Status bar
@@ -112,6 +350,31 @@ fn init_ui(mut commands: Commands, players: Res<Players>, assets: Res<AssetServe
                     // Die 2
                     parent.spawn_bundle(ImageBundle::default()).insert(Die2);
 
+                    // Event die
+                    if setup.ruleset == Ruleset::CitiesAndKnights {
+                        parent
+                            .spawn_bundle(ImageBundle::default())
+                            .insert(EventDieUi);
+                    }
+
+                    // Event deck
+                    if setup.event_deck {
+                        parent
+                            .spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    "",
+                                    TextStyle {
+                                        font: assets.load("FiraSans-Bold.ttf"),
+                                        font_size: DECK_FONT_SIZE,
+                                        color: default(),
+                                    },
+                                    default(),
+                                ),
+                                ..default()
+                            })
+                            .insert(DeckText);
+                    }
+
                     // Status text
                     parent
                         .spawn_bundle(TextBundle {
@@ -127,6 +390,14 @@ fn init_ui(mut commands: Commands, players: Res<Players>, assets: Res<AssetServe
                             ..default()
                         })
                         .insert(StatusBar);
+
+                    // Choice bar
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            color: Color::NONE.into(),
+                            ..default()
+                        })
+                        .insert(ChoiceBar);
                 });
 
             // Right sidebar
@@ -142,6 +413,58 @@ fn init_ui(mut commands: Commands, players: Res<Players>, assets: Res<AssetServe
                     ..default()
                 })
                 .with_children(|parent| {
+                    // Cost card
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::ColumnReverse,
+                                align_items: AlignItems::FlexStart,
+                                ..default()
+                            },
+                            color: Color::NONE.into(),
+                            ..default()
+                        })
+                        .insert(CostCard);
+
+                    // Barbarian track
+                    if setup.ruleset == Ruleset::CitiesAndKnights {
+                        parent
+                            .spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    "",
+                                    TextStyle {
+                                        font: assets.load("FiraSans-Bold.ttf"),
+                                        font_size: BUTTON_FONT_SIZE,
+                                        color: default(),
+                                    },
+                                    default(),
+                                ),
+                                ..default()
+                            })
+                            .insert(BarbarianText);
+                    }
+
+                    // Roll button
+                    parent
+                        .spawn_bundle(ButtonBundle::default())
+                        .insert(RollButton)
+                        .with_children(|parent| {
+                            // Button text
+                            parent.spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    "Roll dice",
+                                    TextStyle {
+                                        font: assets.load("FiraSans-Bold.ttf"),
+                                        font_size: BUTTON_FONT_SIZE,
+                                        color: Color::BLACK,
+                                    },
+                                    default(),
+                                ),
+                                focus_policy: FocusPolicy::Pass,
+                                ..default()
+                            });
+                        });
+
                     // Next button
                     parent
                         .spawn_bundle(ButtonBundle::default())
@@ -167,6 +490,7 @@ fn init_ui(mut commands: Commands, players: Res<Players>, assets: Res<AssetServe
                     parent
                         .spawn_bundle(ButtonBundle::default())
                         .insert(BuildSettlementButton)
+                        .insert(Purchase::Settlement)
                         .with_children(|parent| {
                             // Button text
                             parent.spawn_bundle(TextBundle {
@@ -184,10 +508,33 @@ fn init_ui(mut commands: Commands, players: Res<Players>, assets: Res<AssetServe
                             });
                         });
 
+                    // Build city button
+                    parent
+                        .spawn_bundle(ButtonBundle::default())
+                        .insert(BuildCityButton)
+                        .insert(Purchase::City)
+                        .with_children(|parent| {
+                            // Button text
+                            parent.spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    "Build city",
+                                    TextStyle {
+                                        font: assets.load("FiraSans-Bold.ttf"),
+                                        font_size: BUTTON_FONT_SIZE,
+                                        color: Color::BLACK,
+                                    },
+                                    default(),
+                                ),
+                                focus_policy: FocusPolicy::Pass,
+                                ..default()
+                            });
+                        });
+
                     // Build road button
                     parent
                         .spawn_bundle(ButtonBundle::default())
                         .insert(BuildRoadButton)
+                        .insert(Purchase::Road)
                         .with_children(|parent| {
                             // Button text
                             parent.spawn_bundle(TextBundle {
@@ -204,6 +551,291 @@ fn init_ui(mut commands: Commands, players: Res<Players>, assets: Res<AssetServe
                                 ..default()
                             });
                         });
+
+                    // Ships, if there's sea for them
+                    if presets.get(&setup.board).has_open_sea() {
+                        // Build ship button
+                        parent
+                            .spawn_bundle(ButtonBundle::default())
+                            .insert(BuildShipButton)
+                            .insert(Purchase::Ship)
+                            .with_children(|parent| {
+                                // Button text
+                                parent.spawn_bundle(TextBundle {
+                                    text: Text::with_section(
+                                        "Build ship",
+                                        TextStyle {
+                                            font: assets.load("FiraSans-Bold.ttf"),
+                                            font_size: BUTTON_FONT_SIZE,
+                                            color: Color::BLACK,
+                                        },
+                                        default(),
+                                    ),
+                                    focus_policy: FocusPolicy::Pass,
+                                    ..default()
+                                });
+                            });
+
+                        // Move ship button
+                        parent
+                            .spawn_bundle(ButtonBundle::default())
+                            .insert(MoveShipButton)
+                            .with_children(|parent| {
+                                // Button text
+                                parent.spawn_bundle(TextBundle {
+                                    text: Text::with_section(
+                                        "Move ship",
+                                        TextStyle {
+                                            font: assets.load("FiraSans-Bold.ttf"),
+                                            font_size: BUTTON_FONT_SIZE,
+                                            color: Color::BLACK,
+                                        },
+                                        default(),
+                                    ),
+                                    focus_policy: FocusPolicy::Pass,
+                                    ..default()
+                                });
+                            });
+                    }
+
+                    // Knights and city improvements
+                    if setup.ruleset == Ruleset::CitiesAndKnights {
+                        // Build knight button
+                        parent
+                            .spawn_bundle(ButtonBundle::default())
+                            .insert(BuildKnightButton)
+                            .insert(Purchase::Knight(KnightAction::Build))
+                            .with_children(|parent| {
+                                // Button text
+                                parent.spawn_bundle(TextBundle {
+                                    text: Text::with_section(
+                                        "Build knight",
+                                        TextStyle {
+                                            font: assets.load("FiraSans-Bold.ttf"),
+                                            font_size: BUTTON_FONT_SIZE,
+                                            color: Color::BLACK,
+                                        },
+                                        default(),
+                                    ),
+                                    focus_policy: FocusPolicy::Pass,
+                                    ..default()
+                                });
+                            });
+
+                        // Activate knight button
+                        parent
+                            .spawn_bundle(ButtonBundle::default())
+                            .insert(ActivateKnightButton)
+                            .insert(Purchase::Knight(KnightAction::Activate))
+                            .with_children(|parent| {
+                                // Button text
+                                parent.spawn_bundle(TextBundle {
+                                    text: Text::with_section(
+                                        "Activate knight",
+                                        TextStyle {
+                                            font: assets.load("FiraSans-Bold.ttf"),
+                                            font_size: BUTTON_FONT_SIZE,
+                                            color: Color::BLACK,
+                                        },
+                                        default(),
+                                    ),
+                                    focus_policy: FocusPolicy::Pass,
+                                    ..default()
+                                });
+                            });
+
+                        // Promote knight button
+                        parent
+                            .spawn_bundle(ButtonBundle::default())
+                            .insert(PromoteKnightButton)
+                            .insert(Purchase::Knight(KnightAction::Promote))
+                            .with_children(|parent| {
+                                // Button text
+                                parent.spawn_bundle(TextBundle {
+                                    text: Text::with_section(
+                                        "Promote knight",
+                                        TextStyle {
+                                            font: assets.load("FiraSans-Bold.ttf"),
+                                            font_size: BUTTON_FONT_SIZE,
+                                            color: Color::BLACK,
+                                        },
+                                        default(),
+                                    ),
+                                    focus_policy: FocusPolicy::Pass,
+                                    ..default()
+                                });
+                            });
+
+                        // Improve city button
+                        parent
+                            .spawn_bundle(ButtonBundle::default())
+                            .insert(ImproveCityButton)
+                            .insert(Purchase::ImproveCity)
+                            .with_children(|parent| {
+                                // Button text
+                                parent.spawn_bundle(TextBundle {
+                                    text: Text::with_section(
+                                        "Improve city",
+                                        TextStyle {
+                                            font: assets.load("FiraSans-Bold.ttf"),
+                                            font_size: BUTTON_FONT_SIZE,
+                                            color: Color::BLACK,
+                                        },
+                                        default(),
+                                    ),
+                                    focus_policy: FocusPolicy::Pass,
+                                    ..default()
+                                });
+                            });
+                    }
+
+                    // Cancel button
+                    parent
+                        .spawn_bundle(ButtonBundle::default())
+                        .insert(CancelButton)
+                        .with_children(|parent| {
+                            // Button text
+                            parent.spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    "Cancel",
+                                    TextStyle {
+                                        font: assets.load("FiraSans-Bold.ttf"),
+                                        font_size: BUTTON_FONT_SIZE,
+                                        color: Color::BLACK,
+                                    },
+                                    default(),
+                                ),
+                                focus_policy: FocusPolicy::Pass,
+                                ..default()
+                            });
+                        });
+
+                    // Undo button
+                    parent
+                        .spawn_bundle(ButtonBundle::default())
+                        .insert(UndoButton)
+                        .with_children(|parent| {
+                            // Button text
+                            parent.spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    "Undo",
+                                    TextStyle {
+                                        font: assets.load("FiraSans-Bold.ttf"),
+                                        font_size: BUTTON_FONT_SIZE,
+                                        color: Color::BLACK,
+                                    },
+                                    default(),
+                                ),
+                                focus_policy: FocusPolicy::Pass,
+                                ..default()
+                            });
+                        });
+
+                    // Overlay button
+                    parent
+                        .spawn_bundle(ButtonBundle::default())
+                        .insert(OverlayButton)
+                        .with_children(|parent| {
+                            // Button text
+                            parent.spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    "Odds",
+                                    TextStyle {
+                                        font: assets.load("FiraSans-Bold.ttf"),
+                                        font_size: BUTTON_FONT_SIZE,
+                                        color: Color::BLACK,
+                                    },
+                                    default(),
+                                ),
+                                focus_policy: FocusPolicy::Pass,
+                                ..default()
+                            });
+                        });
+
+                    // Fit board button
+                    parent
+                        .spawn_bundle(ButtonBundle::default())
+                        .insert(FitBoardButton)
+                        .with_children(|parent| {
+                            // Button text
+                            parent.spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    "Fit board",
+                                    TextStyle {
+                                        font: assets.load("FiraSans-Bold.ttf"),
+                                        font_size: BUTTON_FONT_SIZE,
+                                        color: Color::BLACK,
+                                    },
+                                    default(),
+                                ),
+                                focus_policy: FocusPolicy::Pass,
+                                ..default()
+                            });
+                        });
+
+                    // Restart button
+                    parent
+                        .spawn_bundle(ButtonBundle::default())
+                        .insert(RestartButton)
+                        .with_children(|parent| {
+                            // Button text
+                            parent.spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    "Restart",
+                                    TextStyle {
+                                        font: assets.load("FiraSans-Bold.ttf"),
+                                        font_size: BUTTON_FONT_SIZE,
+                                        color: Color::BLACK,
+                                    },
+                                    default(),
+                                ),
+                                focus_policy: FocusPolicy::Pass,
+                                ..default()
+                            });
+                        });
+
+                    // Quit button
+                    parent
+                        .spawn_bundle(ButtonBundle::default())
+                        .insert(QuitButton)
+                        .with_children(|parent| {
+                            // Button text
+                            parent.spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    "Quit to menu",
+                                    TextStyle {
+                                        font: assets.load("FiraSans-Bold.ttf"),
+                                        font_size: BUTTON_FONT_SIZE,
+                                        color: Color::BLACK,
+                                    },
+                                    default(),
+                                ),
+                                focus_policy: FocusPolicy::Pass,
+                                ..default()
+                            });
+                        });
                 });
+
+            // Tooltip
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: assets.load("FiraSans-Bold.ttf"),
+                            font_size: TOOLTIP_FONT_SIZE,
+                            color: default(),
+                        },
+                        default(),
+                    ),
+                    focus_policy: FocusPolicy::Pass,
+                    visibility: Visibility { is_visible: false },
+                    ..default()
+                })
+                .insert(Tooltip);
         });
 }
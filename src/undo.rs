@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::Board,
+    building::{Building, BuildingSlot},
+    color::PlayerColor,
+    commodity::{Commodities, Commodity},
+    improvement::Improvements,
+    knight::{Knight, KnightSlot},
+    menu::in_game,
+    resource::{Hands, RESOURCE_COUNT},
+    road::RoadSlot,
+    ship::{Ship, ShipSlot, ShipsThisTurn},
+    turn::{Players, Turn},
+    ui::UndoButton,
+};
+
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UndoStack>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(forget_actions)
+                .with_system(undo),
+        );
+    }
+}
+
+/// A piece that was put on the board
+#[derive(Clone, Copy)]
+pub enum Placement {
+    Road {
+        index: usize,
+    },
+    Ship {
+        index: usize,
+    },
+    MoveShip {
+        from: usize,
+        to: usize,
+    },
+    /// `previous` is what was in the slot before
+    Building {
+        index: usize,
+        previous: Option<Building>,
+    },
+    /// `previous` is what was in the slot before
+    Knight {
+        index: usize,
+        previous: Option<Knight>,
+    },
+    /// A city improvement, along the track that the commodity pays for
+    Improvement {
+        commodity: Commodity,
+    },
+}
+
+/// Something a player did that can be taken back
+#[derive(Clone, Copy)]
+pub struct Action {
+    pub color: PlayerColor,
+    /// The player's hand before the action
+    pub hand: [u8; RESOURCE_COUNT],
+    pub placement: Option<Placement>,
+}
+
+/// Actions taken in the current build phase.
+/// Only actions that don't reveal anything (ex. builds and bank trades) may be pushed here,
+/// and anything that does reveal something (ex. rolls, card draws, and steals) must clear it.
+#[derive(Default, Deref, DerefMut)]
+pub struct UndoStack(Vec<Action>);
+
+/// Once the player is out of build mode, they can't take anything back.
+/// This includes when the next player gets to build in the special building phase.
+fn forget_actions(turn: Res<Turn>, players: Res<Players>, mut stack: ResMut<UndoStack>) {
+    if turn.is_changed()
+        && !stack.is_empty()
+        && !matches!(
+            *turn,
+            Turn::Build { player, .. }
+            | Turn::BuildRoad { player, .. }
+            | Turn::BuildSettlement { player, .. }
+            | Turn::BuildCity { player, .. }
+            | Turn::BuildShip { player, .. }
+            | Turn::MoveShip { player, .. }
+            | Turn::Knight { player, .. }
+            | Turn::ImproveCity { player, .. }
+            if stack.iter().all(|action| action.color == players[player])
+        )
+    {
+        stack.clear();
+    }
+}
+
+/// If the player presses the undo button or Ctrl+Z in build mode, take back their last action
+fn undo(
+    buttons: Query<&Interaction, (With<UndoButton>, Changed<Interaction>)>,
+    mut roads: Query<&mut RoadSlot>,
+    mut buildings: Query<&mut BuildingSlot>,
+    mut ships: Query<&mut ShipSlot>,
+    mut knights: Query<&mut KnightSlot>,
+    keys: Res<Input<KeyCode>>,
+    board: Res<Board>,
+    players: Res<Players>,
+    turn: Res<Turn>,
+    mut hands: ResMut<Hands>,
+    mut stack: ResMut<UndoStack>,
+    mut ships_this_turn: ResMut<ShipsThisTurn>,
+    mut commodities: ResMut<Commodities>,
+    mut improvements: ResMut<Improvements>,
+) {
+    if let Turn::Build { player, .. } = *turn {
+        let ctrl = keys.pressed(KeyCode::LControl) || keys.pressed(KeyCode::RControl);
+
+        let pressed = (ctrl && keys.just_pressed(KeyCode::Z))
+            || buttons
+                .iter()
+                .any(|interaction| matches!(interaction, Interaction::Clicked));
+
+        if !pressed {
+            return;
+        }
+
+        // The stack should only have this player's actions, but let's not give anyone else's cards back
+        if !matches!(stack.last(), Some(action) if action.color == players[player]) {
+            return;
+        }
+
+        let action = stack.pop().unwrap();
+        hands[action.color as usize] = action.hand;
+
+        match action.placement {
+            Some(Placement::Road { index }) => **roads.get_mut(board.roads[index]).unwrap() = None,
+            Some(Placement::Ship { index }) => {
+                **ships.get_mut(board.ships[index]).unwrap() = None;
+                ships_this_turn.built.retain(|ship| *ship != index);
+            }
+            Some(Placement::MoveShip { from, to }) => {
+                **ships.get_mut(board.ships[to]).unwrap() = None;
+                **ships.get_mut(board.ships[from]).unwrap() = Some(Ship {
+                    color: action.color,
+                });
+                ships_this_turn.moved = false;
+            }
+            Some(Placement::Building { index, previous }) => {
+                **buildings.get_mut(board.buildings[index]).unwrap() = previous
+            }
+            Some(Placement::Knight { index, previous }) => {
+                **knights.get_mut(board.knights[index]).unwrap() = previous
+            }
+            // The improvement cost as many commodities as its level
+            Some(Placement::Improvement { commodity }) => {
+                let level = &mut improvements[action.color as usize][commodity as usize];
+
+                commodities[action.color as usize][commodity as usize] += *level;
+                *level -= 1;
+            }
+            None => (),
+        }
+    }
+}